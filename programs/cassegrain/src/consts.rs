@@ -5,3 +5,25 @@ pub const MANUFACTURER: &[u8] = b"manufacturer";
 pub const EVENT: &[u8] = b"event";
 pub const PROFILE: &[u8] = b"profile";
 pub const BATCH: &[u8] = b"batch";
+pub const TREASURY: &[u8] = b"treasury";
+pub const HANDLER: &[u8] = b"handler";
+pub const UNIT: &[u8] = b"unit";
+pub const UNIT_TREE: &[u8] = b"unit_tree";
+/// Fits a little over a million compressed units per batch
+pub const UNIT_TREE_DEPTH: usize = 20;
+pub const MAX_PARENT_BATCHES: usize = 8;
+pub const BOM: &[u8] = b"bom";
+pub const COMPONENT_USAGE: &[u8] = b"component_usage";
+pub const MAX_BOM_COMPONENTS: usize = 16;
+pub const MAX_METADATA_HISTORY: usize = 8;
+pub const EVENT_PAGE: &[u8] = b"event_page";
+pub const EVENT_PAGE_SIZE: usize = 16;
+pub const ARCHIVE: &[u8] = b"archive";
+/// Events and event pages folded per `archive_product_batch` call
+pub const MAX_ARCHIVE_ACCOUNTS: usize = 16;
+/// Sold batches stay open this long after their last update before they can be archived
+pub const SOLD_RETENTION_PERIOD: i64 = 90 * 24 * 60 * 60;
+pub const MAX_VERIFIERS: usize = 10;
+pub const COUNCIL: &[u8] = b"council";
+pub const PROPOSAL: &[u8] = b"proposal";
+pub const MAX_COUNCIL_MEMBERS: usize = 10;
@@ -1,6 +1,9 @@
 use anchor_lang:: prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
 use crate::state::*;
 use crate::consts::*;
+use crate::error::*;
+use crate::program::Cassegrain;
 
 #[derive(Accounts)]
 pub struct Initialize<'info>{
@@ -10,10 +13,26 @@ pub struct Initialize<'info>{
     init,
     payer = authority,
     space = ANCHOR_DISCRIMINATOR + CassegrainConfig::INIT_SPACE,
-    seeds = [CONFIG, authority.key().as_ref()],
+    seeds = [CONFIG],
     bump,
   )]
   pub cassegrain_config: Account<'info, CassegrainConfig>,
+  /// Program owned fee vault, the default `fee_treasury`
+  #[account(
+    mut,
+    seeds = [TREASURY],
+    bump,
+  )]
+  pub treasury: SystemAccount<'info>,
+  // The config is a singleton, so only the upgrade authority may create it
+  #[account(
+    constraint = program.programdata_address()? == Some(program_data.key()) @CassegrainError::Unauthorized,
+  )]
+  pub program: Program<'info, Cassegrain>,
+  #[account(
+    constraint = program_data.upgrade_authority_address == Some(authority.key()) @CassegrainError::Unauthorized,
+  )]
+  pub program_data: Account<'info, ProgramData>,
   pub system_program: Program<'info, System>
 }
 
@@ -24,23 +43,43 @@ impl <'info> Initialize<'info> {
     max_events_per_product: u32, 
     max_products_per_manufacturer: u32, 
     min_event_interval: i64, 
-    max_batch_size: u8,
+    max_batch_size: u64,
     bumps: InitializeBumps
   ) -> Result<()> {
 
     self.cassegrain_config.set_inner(
       CassegrainConfig { 
         authority: self.authority.key(), 
+        pending_authority: None,
         is_paused: false, 
         product_registration_fee, 
-        fee_treasury: self.authority.key(), 
+        fee_treasury: self.treasury.key(), 
         max_events_per_product, 
         max_products_per_manufacturer, 
         min_event_interval, 
         max_batch_size, 
+        verifiers: Vec::new(),
+        council: None,
         bump: bumps.cassegrain_config
        });
 
+    self.cassegrain_config.validate()?;
+
+    // Fund the vault up to rent exemption so fees below that minimum can land in it
+    let rent_exempt = Rent::get()?.minimum_balance(0);
+    if self.treasury.lamports() < rent_exempt {
+      transfer(
+        CpiContext::new(
+          self.system_program.to_account_info(),
+          Transfer {
+            from: self.authority.to_account_info(),
+            to: self.treasury.to_account_info(),
+          },
+        ),
+        rent_exempt - self.treasury.lamports(),
+      )?;
+    }
+
     Ok(())
   }
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use crate::consts::*;
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG],
+        bump,
+        constraint = cassegrain_config.authority == authority.key() 
+            @ CassegrainError::Unauthorized,
+        constraint = cassegrain_config.council.is_none() 
+            @ CassegrainError::GovernedByCouncil,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub new_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG],
+        bump,
+        constraint = cassegrain_config.pending_authority == Some(new_authority.key()) 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+}
+
+impl<'info> ProposeAuthority<'info> {
+    /// First step of the handover, the proposed key still has to accept
+    pub fn propose_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+        require!(
+            new_authority != self.cassegrain_config.authority,
+            CassegrainError::InvalidAuthority
+        );
+
+        self.cassegrain_config.pending_authority = Some(new_authority);
+
+        emit!(AuthorityProposed {
+            authority: self.authority.key(),
+            pending_authority: new_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_authority_transfer(&mut self) -> Result<()> {
+        require!(
+            self.cassegrain_config.pending_authority.is_some(),
+            CassegrainError::NoPendingAuthority
+        );
+
+        self.cassegrain_config.pending_authority = None;
+        Ok(())
+    }
+}
+
+impl<'info> AcceptAuthority<'info> {
+    pub fn accept_authority(&mut self) -> Result<()> {
+        let previous_authority = self.cassegrain_config.authority;
+
+        self.cassegrain_config.authority = self.new_authority.key();
+        self.cassegrain_config.pending_authority = None;
+
+        emit!(AuthorityTransferred {
+            previous_authority,
+            new_authority: self.new_authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use crate::consts::*;
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct SetBatchEventLimit<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG],
+        bump,
+        constraint = cassegrain_config.authority == authority.key() 
+            @ CassegrainError::Unauthorized,
+        constraint = cassegrain_config.council.is_none() 
+            @ CassegrainError::GovernedByCouncil,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    #[account(
+        mut,
+        seeds = [BATCH, product_batch.manufacturer.as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+}
+
+impl<'info> SetBatchEventLimit<'info> {
+    pub fn set_event_limit(
+        &mut self,
+        batch_id: [u8; 32],
+        max_events: Option<u32>,
+    ) -> Result<()> {
+        let old_limit = self.product_batch.event_limit(&self.cassegrain_config);
+        self.product_batch.set_event_limit_override(max_events)?;
+
+        emit!(BatchEventLimitUpdated {
+            batch_id,
+            updated_by: self.authority.key(),
+            old_limit,
+            new_limit: self.product_batch.event_limit(&self.cassegrain_config),
+            is_override: max_events.is_some(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct BatchEventLimitUpdated {
+    pub batch_id: [u8; 32],
+    pub updated_by: Pubkey,
+    pub old_limit: u32,
+    pub new_limit: u32,
+    pub is_override: bool,
+    pub timestamp: i64,
+}
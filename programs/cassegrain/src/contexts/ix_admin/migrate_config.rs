@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::consts::*;
+use crate::state::*;
+use crate::error::*;
+use crate::helpers::close_raw_account;
+use crate::program::Cassegrain;
+
+/// Layout of `CassegrainConfig` when it was still seeded by `[CONFIG, authority]`
+#[derive(AnchorDeserialize)]
+pub struct LegacyCassegrainConfig {
+    pub authority: Pubkey,
+    pub is_paused: bool,
+    pub product_registration_fee: u64,
+    pub fee_treasury: Pubkey,
+    pub max_events_per_product: u32,
+    pub max_products_per_manufacturer: u32,
+    pub min_event_interval: i64,
+    pub max_batch_size: u8,
+    pub bump: u8,
+}
+
+/// Moves a config created under the old `[CONFIG, authority]` seeds to the
+/// authority independent `[CONFIG]` address and closes the old account.
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Old config account, decoded by hand since its layout predates `pending_authority`
+    #[account(
+        mut,
+        seeds = [CONFIG, authority.key().as_ref()],
+        bump,
+        owner = crate::ID,
+    )]
+    pub legacy_config: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = ANCHOR_DISCRIMINATOR + CassegrainConfig::INIT_SPACE,
+        seeds = [CONFIG],
+        bump,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    // Several legacy configs may exist, the upgrade authority picks the canonical one
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub program: Program<'info, Cassegrain>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateConfig<'info> {
+    pub fn migrate(&mut self, bumps: MigrateConfigBumps) -> Result<()> {
+        let legacy = {
+            let data = self.legacy_config.try_borrow_data()?;
+            require!(
+                data.len() > ANCHOR_DISCRIMINATOR
+                    && &data[..ANCHOR_DISCRIMINATOR] == CassegrainConfig::DISCRIMINATOR,
+                CassegrainError::InvalidLegacyAccount
+            );
+            LegacyCassegrainConfig::deserialize(&mut &data[ANCHOR_DISCRIMINATOR..])?
+        };
+        require!(
+            legacy.authority == self.authority.key(),
+            CassegrainError::Unauthorized
+        );
+
+        self.cassegrain_config.set_inner(CassegrainConfig {
+            authority: legacy.authority,
+            pending_authority: None,
+            is_paused: legacy.is_paused,
+            product_registration_fee: legacy.product_registration_fee,
+            fee_treasury: legacy.fee_treasury,
+            max_events_per_product: legacy.max_events_per_product,
+            max_products_per_manufacturer: legacy.max_products_per_manufacturer,
+            min_event_interval: legacy.min_event_interval,
+            max_batch_size: legacy.max_batch_size as u64,
+            verifiers: Vec::new(),
+            council: None,
+            bump: bumps.cassegrain_config,
+        });
+
+        // Close the legacy account and hand its rent back to the authority
+        close_raw_account(
+            &self.legacy_config.to_account_info(),
+            &self.authority.to_account_info(),
+        )?;
+
+        emit!(ConfigMigrated {
+            legacy_config: self.legacy_config.key(),
+            cassegrain_config: self.cassegrain_config.key(),
+            authority: self.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct ConfigMigrated {
+    pub legacy_config: Pubkey,
+    pub cassegrain_config: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,20 @@
+pub mod update_config;
+pub use update_config::*;
+
+pub mod authority;
+pub use authority::*;
+
+pub mod migrate_config;
+pub use migrate_config::*;
+
+pub mod treasury;
+pub use treasury::*;
+
+pub mod batch_limits;
+pub use batch_limits::*;
+
+pub mod verifiers;
+pub use verifiers::*;
+
+pub mod suspension;
+pub use suspension::*;
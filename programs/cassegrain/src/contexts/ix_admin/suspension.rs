@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use crate::consts::*;
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct ManageSuspension<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG],
+        bump,
+        constraint = cassegrain_config.authority == authority.key() 
+            @ CassegrainError::Unauthorized,
+        constraint = cassegrain_config.council.is_none() 
+            @ CassegrainError::GovernedByCouncil,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    #[account(
+        mut,
+        seeds = [MANUFACTURER, manufacturer.owner.as_ref()],
+        bump,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+}
+
+impl<'info> ManageSuspension<'info> {
+    pub fn suspend(&mut self, reason_code: u16, expires_at: Option<i64>) -> Result<()> {
+        let clock = Clock::get()?;
+        self.manufacturer.suspend(
+            reason_code,
+            self.authority.key(),
+            clock.unix_timestamp,
+            expires_at,
+        )?;
+
+        emit!(ManufacturerSuspended {
+            manufacturer: self.manufacturer.owner,
+            reason_code,
+            suspended_by: self.authority.key(),
+            expires_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn lift(&mut self) -> Result<()> {
+        let suspension = self.manufacturer.lift_suspension()?;
+
+        emit!(SuspensionLifted {
+            manufacturer: self.manufacturer.owner,
+            reason_code: suspension.reason_code,
+            lifted_by: self.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct ManufacturerSuspended {
+    pub manufacturer: Pubkey,
+    pub reason_code: u16,
+    pub suspended_by: Pubkey,
+    pub expires_at: Option<i64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SuspensionLifted {
+    pub manufacturer: Pubkey,
+    pub reason_code: u16,
+    pub lifted_by: Pubkey,
+    pub timestamp: i64,
+}
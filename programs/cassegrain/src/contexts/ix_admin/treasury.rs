@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use crate::consts::*;
+use crate::state::*;
+use crate::error::*;
+use crate::helpers::withdraw_from_treasury;
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG],
+        bump,
+        constraint = cassegrain_config.authority == authority.key() 
+            @ CassegrainError::Unauthorized,
+        constraint = cassegrain_config.council.is_none() 
+            @ CassegrainError::GovernedByCouncil,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    #[account(
+        mut,
+        seeds = [TREASURY],
+        bump,
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    /// CHECK: Any account chosen by the authority to receive the fees
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawFees<'info> {
+    pub fn withdraw(&mut self, amount: u64, bumps: WithdrawFeesBumps) -> Result<()> {
+        withdraw_from_treasury(
+            &self.treasury.to_account_info(),
+            &self.destination.to_account_info(),
+            &self.system_program.to_account_info(),
+            bumps.treasury,
+            amount,
+        )?;
+
+        emit!(FeesWithdrawn {
+            authority: self.authority.key(),
+            destination: self.destination.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct FeesWithdrawn {
+    pub authority: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+use crate::consts::*;
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG],
+        bump,
+        constraint = cassegrain_config.authority == authority.key() 
+            @ CassegrainError::Unauthorized,
+        constraint = cassegrain_config.council.is_none() 
+            @ CassegrainError::GovernedByCouncil,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+}
+
+impl<'info> UpdateConfig<'info> {
+    pub fn update_config(&mut self, update: ConfigUpdate) -> Result<()> {
+        let old = (*self.cassegrain_config).clone();
+
+        self.cassegrain_config.apply_update(&update)?;
+
+        emit!(ConfigUpdated::from_change(
+            self.authority.key(),
+            &old,
+            &self.cassegrain_config,
+            Clock::get()?.unix_timestamp,
+        ));
+
+        Ok(())
+    }
+
+    pub fn set_paused(&mut self, paused: bool) -> Result<()> {
+        if paused {
+            require!(!self.cassegrain_config.is_paused, CassegrainError::ProgramPaused);
+        } else {
+            require!(self.cassegrain_config.is_paused, CassegrainError::ProgramNotPaused);
+        }
+
+        let old = (*self.cassegrain_config).clone();
+        self.cassegrain_config.is_paused = paused;
+
+        emit!(ConfigUpdated::from_change(
+            self.authority.key(),
+            &old,
+            &self.cassegrain_config,
+            Clock::get()?.unix_timestamp,
+        ));
+
+        Ok(())
+    }
+
+    /// Redirect future registration fees, fees already collected stay where they are
+    pub fn set_fee_treasury(&mut self, fee_treasury: Pubkey) -> Result<()> {
+        require!(fee_treasury != Pubkey::default(), CassegrainError::InvalidFeeTreasury);
+
+        let old_fee_treasury = self.cassegrain_config.fee_treasury;
+        self.cassegrain_config.fee_treasury = fee_treasury;
+
+        emit!(FeeTreasuryUpdated {
+            updated_by: self.authority.key(),
+            old_fee_treasury,
+            new_fee_treasury: fee_treasury,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct FeeTreasuryUpdated {
+    pub updated_by: Pubkey,
+    pub old_fee_treasury: Pubkey,
+    pub new_fee_treasury: Pubkey,
+    pub timestamp: i64,
+}
+
+// Emitted on every config change with the values before and after
+#[event]
+pub struct ConfigUpdated {
+    pub updated_by: Pubkey,
+    pub old_is_paused: bool,
+    pub new_is_paused: bool,
+    pub old_product_registration_fee: u64,
+    pub new_product_registration_fee: u64,
+    pub old_max_events_per_product: u32,
+    pub new_max_events_per_product: u32,
+    pub old_max_products_per_manufacturer: u32,
+    pub new_max_products_per_manufacturer: u32,
+    pub old_min_event_interval: i64,
+    pub new_min_event_interval: i64,
+    pub old_max_batch_size: u64,
+    pub new_max_batch_size: u64,
+    pub timestamp: i64,
+}
+
+impl ConfigUpdated {
+    pub fn from_change(
+        updated_by: Pubkey,
+        old: &CassegrainConfig,
+        new: &CassegrainConfig,
+        timestamp: i64,
+    ) -> Self {
+        Self {
+            updated_by,
+            old_is_paused: old.is_paused,
+            new_is_paused: new.is_paused,
+            old_product_registration_fee: old.product_registration_fee,
+            new_product_registration_fee: new.product_registration_fee,
+            old_max_events_per_product: old.max_events_per_product,
+            new_max_events_per_product: new.max_events_per_product,
+            old_max_products_per_manufacturer: old.max_products_per_manufacturer,
+            new_max_products_per_manufacturer: new.max_products_per_manufacturer,
+            old_min_event_interval: old.min_event_interval,
+            new_min_event_interval: new.min_event_interval,
+            old_max_batch_size: old.max_batch_size,
+            new_max_batch_size: new.max_batch_size,
+            timestamp,
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use crate::consts::*;
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct ManageVerifiers<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG],
+        bump,
+        constraint = cassegrain_config.authority == authority.key() 
+            @ CassegrainError::Unauthorized,
+        constraint = cassegrain_config.council.is_none() 
+            @ CassegrainError::GovernedByCouncil,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+}
+
+impl<'info> ManageVerifiers<'info> {
+    pub fn add_verifier(&mut self, verifier: Pubkey) -> Result<()> {
+        self.cassegrain_config.add_verifier(verifier)?;
+
+        emit!(VerifierAdded {
+            verifier,
+            appointed_by: self.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn remove_verifier(&mut self, verifier: Pubkey) -> Result<()> {
+        self.cassegrain_config.remove_verifier(&verifier)?;
+
+        emit!(VerifierRemoved {
+            verifier,
+            removed_by: self.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct VerifierAdded {
+    pub verifier: Pubkey,
+    pub appointed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VerifierRemoved {
+    pub verifier: Pubkey,
+    pub removed_by: Pubkey,
+    pub timestamp: i64,
+}
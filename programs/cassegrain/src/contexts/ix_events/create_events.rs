@@ -9,32 +9,50 @@ pub struct CreateEvent<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
 
-    /// CHECK: The authority of this program  
-    pub authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [BATCH, product_batch.manufacturer.as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
 
+    /// Current tail of the batch's chain, required once the batch has events
+    #[account(
+        mut,
+        constraint = product_batch.tail_event == Some(previous_product_event.key()) 
+            @ CassegrainError::InvalidEventChain,
+    )]
+    pub previous_product_event: Option<Account<'info, ProductEvent>>,
+
+    /// Event number `total_events` of the batch
     #[account(
         init,
         payer = signer,
         space = ANCHOR_DISCRIMINATOR + ProductEvent::INIT_SPACE,
-        seeds = [EVENT, event_id.as_ref()],
+        seeds = [EVENT, product_batch.key().as_ref(), product_batch.total_events.to_le_bytes().as_ref()],
         bump,
     )]
     pub events: Account<'info, ProductEvent>,
 
+    /// Required when the signer is not the batch manufacturer
     #[account(
-        mut,
-        seeds = [BATCH, batch_id.as_ref()],
-        bump,
-        constraint = product_batch.manufacturer == signer.key() 
-            @ CassegrainError::Unauthorized,
+        seeds = [HANDLER, product_batch.key().as_ref(), signer.key().as_ref()],
+        bump = batch_handler.bump,
     )]
-    pub product_batch: Account<'info, ProductBatch>,
+    pub batch_handler: Option<Account<'info, BatchHandler>>,
 
+    /// Target unit, omit for events about the whole batch
     #[account(
-        seeds = [CONFIG, authority.key().as_ref()],
+        seeds = [UNIT, product_batch.key().as_ref(), product_unit.serial_number.as_ref()],
+        bump = product_unit.bump,
+        constraint = product_unit.batch == product_batch.key() 
+            @ CassegrainError::InvalidUnit,
+    )]
+    pub product_unit: Option<Account<'info, ProductUnit>>,
+
+    #[account(
+        seeds = [CONFIG],
         bump,
-        constraint = cassegrain_config.authority == authority.key() 
-            @ CassegrainError::Unauthorized,
         constraint = !cassegrain_config.is_paused 
             @ CassegrainError::ProgramPaused,
     )]
@@ -45,13 +63,23 @@ pub struct CreateEvent<'info> {
         bump,
         constraint = manufacturer.owner == signer.key() 
             @ CassegrainError::Unauthorized,
+        constraint = manufacturer.is_verified 
+            @ CassegrainError::ManufacturerNotVerified,
     )]
     pub manufacturer: Account<'info, ManufacturerProfile>,
 
+    /// Profile the batch is registered under, a suspended owner freezes its batches
+    #[account(
+        seeds = [MANUFACTURER, product_batch.manufacturer.as_ref()],
+        bump,
+    )]
+    pub batch_owner: Account<'info, ManufacturerProfile>,
+
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> CreateEvent<'info> {
+    #[allow(clippy::too_many_arguments)]
     pub fn create_event(
         &mut self,
         batch_id: [u8; 32],
@@ -60,11 +88,21 @@ impl<'info> CreateEvent<'info> {
         metadata_ipfs: Option<String>,
         order_status: OrderStatus,
         previous_event: Option<Pubkey>,
+        quantity: Option<u64>,
+        location: Option<Location>,
         bumps: CreateEventBumps,
     ) -> Result<()> {
         let clock = Clock::get()?;
         let config = &self.cassegrain_config;
         
+        // Only the manufacturer or a granted handler with the right role may log
+        self.manufacturer.require_active(clock.unix_timestamp)?;
+        self.batch_owner.require_active(clock.unix_timestamp)?;
+        self.product_batch
+            .authorize_actor(&self.signer.key(), self.batch_handler.as_deref())?;
+        event_type.authorize(self.manufacturer.business_type)?;
+        event_type.check_location(location.as_ref())?;
+
         // Basic validation checks only
         if let Some(ref ipfs) = metadata_ipfs {
             require!(
@@ -82,9 +120,20 @@ impl<'info> CreateEvent<'info> {
             );
         }
 
+        self.product_batch.transition_order_status(order_status)?;
+
+        // Only the current tail may be extended, and it must be passed in to be linked
+        require!(
+            previous_event == self.product_batch.tail_event
+                && previous_event.is_some() == self.previous_product_event.is_some(),
+            CassegrainError::InvalidEventChain
+        );
+
         // Create the event
         self.events.set_inner(ProductEvent {
             event_id,
+            sequence: self.product_batch.total_events,
+            history_index: self.product_batch.history_len(),
             batch_id,
             product_event_type: event_type,
             actor: self.signer.key(),
@@ -94,18 +143,36 @@ impl<'info> CreateEvent<'info> {
             order_status,
             previous_event,
             next_event: None,
+            unit: self.product_unit.as_ref().map(|unit| unit.key()),
+            quantity,
+            location,
+            prev_hash: self.product_batch.history_digest,
+            event_hash: [0u8; 32],
             bumps: bumps.events,
         });
+        self.events.event_hash = self.events.compute_hash()?;
+        self.product_batch.history_digest = self.events.event_hash;
+
+        // Link the chain both ways
+        let event_key = self.events.key();
+        self.product_batch.link_event(event_key);
+        if let Some(previous) = self.previous_product_event.as_mut() {
+            previous.next_event = Some(event_key);
+        }
 
         // Update product batch
-        self.product_batch.total_events += 1;
-        self.product_batch.last_updated = clock.unix_timestamp;
+        self.product_batch.apply_event_quantity(event_type, quantity)?;
+        self.product_batch.record_event(config, clock.unix_timestamp)?;
 
         // Emit event for off-chain tracking
         emit!(EventCreated {
             event_id,
+            sequence: self.events.sequence,
             batch_id,
             event_type,
+            unit: self.events.unit,
+            quantity,
+            location: self.events.location.clone(),
             actor: self.signer.key(),
             timestamp: clock.unix_timestamp,
         });
@@ -118,8 +185,12 @@ impl<'info> CreateEvent<'info> {
 #[event]
 pub struct EventCreated {
     pub event_id: [u8; 32],
+    pub sequence: u32,
     pub batch_id: [u8; 32],
     pub event_type: EventType,
+    pub unit: Option<Pubkey>,
+    pub quantity: Option<u64>,
+    pub location: Option<Location>,
     pub actor: Pubkey,
     pub timestamp: i64,
 }
\ No newline at end of file
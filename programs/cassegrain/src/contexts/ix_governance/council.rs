@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use crate::consts::*;
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct CreateCouncil<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG],
+        bump,
+        constraint = cassegrain_config.authority == authority.key() 
+            @ CassegrainError::Unauthorized,
+        constraint = cassegrain_config.council.is_none() 
+            @ CassegrainError::GovernedByCouncil,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = ANCHOR_DISCRIMINATOR + AdminCouncil::INIT_SPACE,
+        seeds = [COUNCIL],
+        bump,
+    )]
+    pub council: Account<'info, AdminCouncil>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateCouncil<'info> {
+    pub fn create(
+        &mut self,
+        members: Vec<Pubkey>,
+        threshold: u8,
+        bumps: CreateCouncilBumps,
+    ) -> Result<()> {
+        AdminCouncil::validate_members(&members, threshold)?;
+
+        self.council.set_inner(AdminCouncil {
+            members: members.clone(),
+            threshold,
+            proposal_count: 0,
+            bump: bumps.council,
+        });
+        self.cassegrain_config.council = Some(self.council.key());
+        // A handover started by the single key must not complete behind the council
+        self.cassegrain_config.pending_authority = None;
+
+        emit!(CouncilMembersChanged {
+            council: self.council.key(),
+            members,
+            threshold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct CouncilMembersChanged {
+    pub council: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
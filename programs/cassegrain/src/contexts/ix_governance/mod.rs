@@ -0,0 +1,5 @@
+pub mod council;
+pub use council::*;
+
+pub mod proposal;
+pub use proposal::*;
@@ -0,0 +1,343 @@
+use anchor_lang::prelude::*;
+use crate::consts::*;
+use crate::state::*;
+use crate::error::*;
+use crate::helpers::withdraw_from_treasury;
+use crate::contexts::ix_admin::{
+    AuthorityProposed, BatchEventLimitUpdated, ConfigUpdated, FeeTreasuryUpdated, FeesWithdrawn,
+    ManufacturerSuspended, SuspensionLifted, VerifierAdded, VerifierRemoved,
+};
+use crate::contexts::ix_registry::VerificationRevoked;
+use super::CouncilMembersChanged;
+
+#[derive(Accounts)]
+pub struct Propose<'info> {
+    #[account(mut)]
+    pub member: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [COUNCIL],
+        bump = council.bump,
+        constraint = council.is_member(&member.key()) 
+            @ CassegrainError::NotCouncilMember,
+    )]
+    pub council: Account<'info, AdminCouncil>,
+
+    #[account(
+        init,
+        payer = member,
+        space = ANCHOR_DISCRIMINATOR + CouncilProposal::INIT_SPACE,
+        seeds = [PROPOSAL, council.proposal_count.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub proposal: Account<'info, CouncilProposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    pub member: Signer<'info>,
+
+    #[account(
+        seeds = [COUNCIL],
+        bump = council.bump,
+        constraint = council.is_member(&member.key()) 
+            @ CassegrainError::NotCouncilMember,
+    )]
+    pub council: Account<'info, AdminCouncil>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL, proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        constraint = !proposal.executed 
+            @ CassegrainError::ProposalAlreadyExecuted,
+    )]
+    pub proposal: Account<'info, CouncilProposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    pub member: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [COUNCIL],
+        bump = council.bump,
+        constraint = council.is_member(&member.key()) 
+            @ CassegrainError::NotCouncilMember,
+    )]
+    pub council: Account<'info, AdminCouncil>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL, proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        constraint = !proposal.executed 
+            @ CassegrainError::ProposalAlreadyExecuted,
+    )]
+    pub proposal: Account<'info, CouncilProposal>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG],
+        bump,
+        constraint = cassegrain_config.council == Some(council.key()) 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Propose<'info> {
+    pub fn propose(&mut self, action: CouncilAction, bumps: ProposeBumps) -> Result<()> {
+        if let CouncilAction::ChangeMembers { members, threshold } = &action {
+            AdminCouncil::validate_members(members, *threshold)?;
+        }
+
+        let clock = Clock::get()?;
+        let index = self.council.proposal_count;
+
+        // The proposer's approval is implied
+        self.proposal.set_inner(CouncilProposal {
+            index,
+            proposer: self.member.key(),
+            action: action.clone(),
+            approvals: vec![self.member.key()],
+            created_at: clock.unix_timestamp,
+            executed: false,
+            bump: bumps.proposal,
+        });
+        self.council.proposal_count = index
+            .checked_add(1)
+            .ok_or(CassegrainError::MathOverflow)?;
+
+        emit!(ProposalCreated {
+            proposal: self.proposal.key(),
+            index,
+            proposer: self.member.key(),
+            action,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> ApproveProposal<'info> {
+    pub fn approve(&mut self) -> Result<()> {
+        let member = self.member.key();
+        require!(
+            !self.proposal.approvals.contains(&member),
+            CassegrainError::AlreadyApproved
+        );
+
+        // Drop approvals of removed members so the list never outgrows the council
+        let council = &self.council;
+        self.proposal.approvals.retain(|key| council.is_member(key));
+        self.proposal.approvals.push(member);
+
+        emit!(ProposalApproved {
+            proposal: self.proposal.key(),
+            index: self.proposal.index,
+            member,
+            approvals: self.proposal.approvals.len() as u8,
+            threshold: self.council.threshold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> ExecuteProposal<'info> {
+    /// Actions that touch accounts other than the config take them through
+    /// `remaining`, in the order given by `CouncilAction::expected_accounts`
+    pub fn execute(&mut self, remaining: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(
+            self.council.approvals_counted(&self.proposal) >= self.council.threshold as usize,
+            CassegrainError::ThresholdNotReached
+        );
+
+        let expected = self.proposal.action.expected_accounts();
+        require!(
+            remaining.len() == expected.len()
+                && remaining.iter().zip(&expected).all(|(info, key)| info.key == key),
+            CassegrainError::InvalidProposalAccounts
+        );
+
+        let clock = Clock::get()?;
+        let council_key = self.council.key();
+        let old = (*self.cassegrain_config).clone();
+        let config = &mut self.cassegrain_config;
+
+        match self.proposal.action.clone() {
+            CouncilAction::UpdateConfig(update) => {
+                config.apply_update(&update)?;
+                emit!(ConfigUpdated::from_change(council_key, &old, config, clock.unix_timestamp));
+            }
+            CouncilAction::SetPaused(paused) => {
+                require!(config.is_paused != paused, CassegrainError::InvalidConfigValue);
+                config.is_paused = paused;
+                emit!(ConfigUpdated::from_change(council_key, &old, config, clock.unix_timestamp));
+            }
+            CouncilAction::SetFeeTreasury(fee_treasury) => {
+                require!(fee_treasury != Pubkey::default(), CassegrainError::InvalidFeeTreasury);
+                config.fee_treasury = fee_treasury;
+                emit!(FeeTreasuryUpdated {
+                    updated_by: council_key,
+                    old_fee_treasury: old.fee_treasury,
+                    new_fee_treasury: fee_treasury,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+            CouncilAction::AddVerifier(verifier) => {
+                config.add_verifier(verifier)?;
+                emit!(VerifierAdded {
+                    verifier,
+                    appointed_by: council_key,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+            CouncilAction::RemoveVerifier(verifier) => {
+                config.remove_verifier(&verifier)?;
+                emit!(VerifierRemoved {
+                    verifier,
+                    removed_by: council_key,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+            CouncilAction::ChangeMembers { members, threshold } => {
+                AdminCouncil::validate_members(&members, threshold)?;
+                self.council.members = members.clone();
+                self.council.threshold = threshold;
+                emit!(CouncilMembersChanged {
+                    council: council_key,
+                    members,
+                    threshold,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+            CouncilAction::WithdrawFees { amount, destination } => {
+                let (_, treasury_bump) = Pubkey::find_program_address(&[TREASURY], &crate::ID);
+                withdraw_from_treasury(
+                    &remaining[0],
+                    &remaining[1],
+                    &self.system_program.to_account_info(),
+                    treasury_bump,
+                    amount,
+                )?;
+                emit!(FeesWithdrawn {
+                    authority: council_key,
+                    destination,
+                    amount,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+            CouncilAction::ProposeAuthority(new_authority) => {
+                require!(new_authority != config.authority, CassegrainError::InvalidAuthority);
+                config.pending_authority = Some(new_authority);
+                emit!(AuthorityProposed {
+                    authority: council_key,
+                    pending_authority: new_authority,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+            CouncilAction::CancelAuthorityTransfer => {
+                require!(config.pending_authority.is_some(), CassegrainError::NoPendingAuthority);
+                config.pending_authority = None;
+            }
+            CouncilAction::SuspendManufacturer { manufacturer, reason_code, expires_at } => {
+                let mut profile = Account::<ManufacturerProfile>::try_from(&remaining[0])?;
+                profile.suspend(reason_code, council_key, clock.unix_timestamp, expires_at)?;
+                profile.exit(&crate::ID)?;
+                emit!(ManufacturerSuspended {
+                    manufacturer,
+                    reason_code,
+                    suspended_by: council_key,
+                    expires_at,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+            CouncilAction::LiftSuspension(manufacturer) => {
+                let mut profile = Account::<ManufacturerProfile>::try_from(&remaining[0])?;
+                let suspension = profile.lift_suspension()?;
+                profile.exit(&crate::ID)?;
+                emit!(SuspensionLifted {
+                    manufacturer,
+                    reason_code: suspension.reason_code,
+                    lifted_by: council_key,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+            CouncilAction::RevokeVerification(manufacturer) => {
+                let mut profile = Account::<ManufacturerProfile>::try_from(&remaining[0])?;
+                require!(profile.is_verified, CassegrainError::ManufacturerNotVerified);
+                let previous_verifier = profile.verified_by;
+                profile.clear_verification();
+                profile.exit(&crate::ID)?;
+                emit!(VerificationRevoked {
+                    manufacturer,
+                    revoked_by: council_key,
+                    previous_verifier,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+            CouncilAction::SetBatchEventLimit { batch_id, max_events, .. } => {
+                let mut batch = Account::<ProductBatch>::try_from(&remaining[0])?;
+                let old_limit = batch.event_limit(config);
+                batch.set_event_limit_override(max_events)?;
+                batch.exit(&crate::ID)?;
+                emit!(BatchEventLimitUpdated {
+                    batch_id,
+                    updated_by: council_key,
+                    old_limit,
+                    new_limit: batch.event_limit(config),
+                    is_override: max_events.is_some(),
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+        }
+
+        self.proposal.executed = true;
+
+        emit!(ProposalExecuted {
+            proposal: self.proposal.key(),
+            index: self.proposal.index,
+            executed_by: self.member.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: CouncilAction,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub member: Pubkey,
+    pub approvals: u8,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal: Pubkey,
+    pub index: u64,
+    pub executed_by: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,285 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::state::*;
+use crate::error::*;
+use crate::consts::*;
+use crate::helpers::close_raw_account;
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct ArchiveProductBatch<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR + BatchArchive::INIT_SPACE,
+        seeds = [ARCHIVE, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub batch_archive: Account<'info, BatchArchive>,
+
+    #[account(
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct FinalizeArchive<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        close = signer,
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+
+    #[account(
+        mut,
+        seeds = [ARCHIVE, signer.key().as_ref(), batch_id.as_ref()],
+        bump = batch_archive.bump,
+    )]
+    pub batch_archive: Account<'info, BatchArchive>,
+
+    /// CHECK: Page after the last one archived, pages are opened in order so
+    /// it being empty means none are left
+    #[account(
+        seeds = [EVENT_PAGE, product_batch.key().as_ref(), batch_archive.pages_archived.to_le_bytes().as_ref()],
+        bump,
+        constraint = next_event_page.data_is_empty()
+            @ CassegrainError::BatchAccountsOpen,
+    )]
+    pub next_event_page: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+}
+
+impl<'info> ArchiveProductBatch<'info> {
+    /// Folds the next slice of the batch's history into the tombstone and closes it.
+    /// Remaining accounts are event accounts and event pages in history order: the
+    /// next event from the archive cursor, or the current page, which is folded up to
+    /// the next event and closed once all of its records are in. Units, the unit tree,
+    /// handler grants and the bill of materials of the batch can be passed in any
+    /// order and are closed as they come.
+    pub fn archive(
+        &mut self,
+        batch_id: [u8; 32],
+        remaining: &'info [AccountInfo<'info>],
+        bumps: ArchiveProductBatchBumps,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        // A suspended manufacturer's history stays readable until the suspension ends
+        self.manufacturer.require_active(clock.unix_timestamp)?;
+        require!(
+            self.product_batch.is_archivable(clock.unix_timestamp),
+            CassegrainError::BatchNotArchivable
+        );
+        require!(
+            remaining.len() <= MAX_ARCHIVE_ACCOUNTS,
+            CassegrainError::TooManyArchiveAccounts
+        );
+
+        let batch_key = self.product_batch.key();
+        if self.batch_archive.batch == Pubkey::default() {
+            self.batch_archive.set_inner(BatchArchive {
+                batch: batch_key,
+                batch_id,
+                manufacturer: self.product_batch.manufacturer,
+                final_status: self.product_batch.status,
+                parent_batches: self.product_batch.parent_batches.clone(),
+                total_events: 0,
+                events_archived: 0,
+                rollup_records: 0,
+                records_archived: 0,
+                pages_archived: 0,
+                units_closed: 0,
+                unit_tree_closed: false,
+                handlers_closed: 0,
+                bom_accounts_closed: 0,
+                next_event: None,
+                history_digest: [0u8; 32],
+                digest: [0u8; 32],
+                created_at: self.product_batch.created_at,
+                archived_at: None,
+                bump: bumps.batch_archive,
+            });
+        }
+
+        let signer_info = self.signer.to_account_info();
+        let batch = &self.product_batch;
+        let archive = &mut self.batch_archive;
+
+        for info in remaining {
+            let position = archive.position();
+
+            if archive.expected_event(batch) == Some(info.key()) {
+                let event = Account::<ProductEvent>::try_from(info)?;
+                require!(
+                    event.batch_id == batch_id && event.history_index == position,
+                    CassegrainError::InvalidEventChain
+                );
+
+                archive.history_digest = event.verify_link(&archive.history_digest)?;
+                archive.digest = BatchArchive::digest_account(&archive.digest, &info.try_borrow_data()?);
+                archive.next_event = event.next_event;
+                archive.events_archived += 1;
+                close_raw_account(info, &signer_info)?;
+                continue;
+            }
+
+            let discriminator = info
+                .try_borrow_data()?
+                .get(..ANCHOR_DISCRIMINATOR)
+                .map(<[u8]>::to_vec)
+                .unwrap_or_default();
+            match discriminator.as_slice() {
+                d if d == EventPage::DISCRIMINATOR => {
+                    let page = Account::<EventPage>::try_from(info)?;
+                    require!(
+                        page.batch == batch_key && page.page_index == archive.pages_archived,
+                        CassegrainError::InvalidEventPage
+                    );
+
+                    // Opened after the last record, there is nothing left to fold
+                    if archive.records_archived == batch.rollup_records {
+                        require!(page.records.is_empty(), CassegrainError::InvalidEventPage);
+                        archive.digest = BatchArchive::digest_account(&archive.digest, &info.try_borrow_data()?);
+                        archive.pages_archived += 1;
+                        close_raw_account(info, &signer_info)?;
+                        continue;
+                    }
+
+                    // Records of this page up to the next event
+                    let offset = (archive.records_archived - archive.pages_archived * EVENT_PAGE_SIZE as u32) as usize;
+                    let (digest, _, replayed) = replay_history(
+                        archive.history_digest,
+                        position,
+                        &mut std::iter::empty().peekable(),
+                        &mut page.records[offset..].iter().peekable(),
+                    )?;
+                    require!(replayed > 0, CassegrainError::InvalidEventPage);
+                    archive.history_digest = digest;
+                    archive.records_archived += replayed;
+
+                    let folded = offset + replayed as usize == page.records.len();
+                    if folded && (page.records.len() == EVENT_PAGE_SIZE || archive.records_archived == batch.rollup_records) {
+                        archive.digest = BatchArchive::digest_account(&archive.digest, &info.try_borrow_data()?);
+                        archive.pages_archived += 1;
+                        close_raw_account(info, &signer_info)?;
+                    }
+                }
+                d if d == ProductUnit::DISCRIMINATOR => {
+                    let unit = Account::<ProductUnit>::try_from(info)?;
+                    require_keys_eq!(unit.batch, batch_key, CassegrainError::InvalidArchiveAccount);
+                    archive.units_closed += 1;
+                    close_raw_account(info, &signer_info)?;
+                }
+                d if d == UnitTree::DISCRIMINATOR => {
+                    let tree = Account::<UnitTree>::try_from(info)?;
+                    require_keys_eq!(tree.batch, batch_key, CassegrainError::InvalidArchiveAccount);
+                    archive.unit_tree_closed = true;
+                    close_raw_account(info, &signer_info)?;
+                }
+                d if d == BatchHandler::DISCRIMINATOR => {
+                    let handler = Account::<BatchHandler>::try_from(info)?;
+                    require_keys_eq!(handler.batch, batch_key, CassegrainError::InvalidArchiveAccount);
+                    archive.handlers_closed += 1;
+                    close_raw_account(info, &signer_info)?;
+                }
+                d if d == BillOfMaterials::DISCRIMINATOR => {
+                    let bom = Account::<BillOfMaterials>::try_from(info)?;
+                    require_keys_eq!(bom.assembly, batch_key, CassegrainError::InvalidArchiveAccount);
+                    archive.bom_accounts_closed += 1;
+                    close_raw_account(info, &signer_info)?;
+                }
+                d if d == ComponentUsage::DISCRIMINATOR => {
+                    let usage = Account::<ComponentUsage>::try_from(info)?;
+                    require_keys_eq!(usage.assembly, batch_key, CassegrainError::InvalidArchiveAccount);
+                    archive.bom_accounts_closed += 1;
+                    close_raw_account(info, &signer_info)?;
+                }
+                _ => return err!(CassegrainError::InvalidArchiveAccount),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'info> FinalizeArchive<'info> {
+    /// Seals the digest with the batch data and closes the batch once its history is
+    /// folded and everything else it owns is closed
+    pub fn finalize(&mut self, batch_id: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        self.manufacturer.require_active(clock.unix_timestamp)?;
+        let batch = &self.product_batch;
+        require!(
+            batch.is_archivable(clock.unix_timestamp),
+            CassegrainError::BatchNotArchivable
+        );
+        require!(
+            self.batch_archive.is_complete(batch),
+            CassegrainError::InvalidEventChain
+        );
+        require!(
+            self.batch_archive.dependents_closed(batch),
+            CassegrainError::BatchAccountsOpen
+        );
+
+        let archive = &mut self.batch_archive;
+        archive.digest = BatchArchive::seal(&archive.digest, &batch.to_account_info().try_borrow_data()?);
+        archive.final_status = batch.status;
+        archive.total_events = batch.total_events;
+        archive.rollup_records = batch.rollup_records;
+        archive.archived_at = Some(clock.unix_timestamp);
+
+        self.manufacturer.close_batch()?;
+
+        emit!(ProductBatchArchived {
+            batch_id,
+            manufacturer: self.signer.key(),
+            final_status: archive.final_status,
+            events_archived: archive.events_archived,
+            rollup_records: archive.rollup_records,
+            digest: archive.digest,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct ProductBatchArchived {
+    pub batch_id: [u8; 32],
+    pub manufacturer: Pubkey,
+    pub final_status: ProductStatus,
+    pub events_archived: u32,
+    pub rollup_records: u32,
+    pub digest: [u8; 32],
+    pub timestamp: i64,
+}
@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::consts::*;
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct GrantBatchAccess<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+        constraint = product_batch.manufacturer == signer.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+
+    /// Profile of the distributor, inspector, retailer... being granted access
+    #[account(
+        seeds = [MANUFACTURER, handler_profile.owner.as_ref()],
+        bump,
+        constraint = handler_profile.owner != signer.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub handler_profile: Account<'info, ManufacturerProfile>,
+
+    #[account(
+        init,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR + BatchHandler::INIT_SPACE,
+        seeds = [HANDLER, product_batch.key().as_ref(), handler_profile.owner.as_ref()],
+        bump,
+    )]
+    pub batch_handler: Account<'info, BatchHandler>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct RevokeBatchAccess<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+        constraint = product_batch.manufacturer == signer.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+
+    #[account(
+        mut,
+        close = signer,
+        seeds = [HANDLER, product_batch.key().as_ref(), batch_handler.handler.as_ref()],
+        bump = batch_handler.bump,
+    )]
+    pub batch_handler: Account<'info, BatchHandler>,
+}
+
+impl<'info> GrantBatchAccess<'info> {
+    pub fn grant(&mut self, batch_id: [u8; 32], bumps: GrantBatchAccessBumps) -> Result<()> {
+        let clock = Clock::get()?;
+        self.product_batch.handlers_granted = self
+            .product_batch
+            .handlers_granted
+            .checked_add(1)
+            .ok_or(CassegrainError::MathOverflow)?;
+
+        self.batch_handler.set_inner(BatchHandler {
+            batch: self.product_batch.key(),
+            handler: self.handler_profile.owner,
+            granted_by: self.signer.key(),
+            granted_at: clock.unix_timestamp,
+            bump: bumps.batch_handler,
+        });
+
+        emit!(BatchAccessGranted {
+            batch_id,
+            handler: self.handler_profile.owner,
+            business_type: self.handler_profile.business_type,
+            granted_by: self.signer.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> RevokeBatchAccess<'info> {
+    pub fn revoke(&mut self, batch_id: [u8; 32]) -> Result<()> {
+        self.product_batch.handlers_granted = self
+            .product_batch
+            .handlers_granted
+            .checked_sub(1)
+            .ok_or(CassegrainError::MathOverflow)?;
+
+        emit!(BatchAccessRevoked {
+            batch_id,
+            handler: self.batch_handler.handler,
+            revoked_by: self.signer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct BatchAccessGranted {
+    pub batch_id: [u8; 32],
+    pub handler: Pubkey,
+    pub business_type: BusinessType,
+    pub granted_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchAccessRevoked {
+    pub batch_id: [u8; 32],
+    pub handler: Pubkey,
+    pub revoked_by: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use crate::consts::*;
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct UpdateBatchMetadata<'info> {
+    pub signer: Signer<'info>,
+
+    /// Only the manufacturer's own batch derives from these seeds
+    #[account(
+        mut,
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+
+    #[account(
+        seeds = [CONFIG],
+        bump,
+        constraint = !cassegrain_config.is_paused
+            @ CassegrainError::ProgramPaused,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    #[account(
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key()
+            @ CassegrainError::Unauthorized,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+}
+
+impl<'info> UpdateBatchMetadata<'info> {
+    pub fn update_metadata(&mut self, batch_id: [u8; 32], metadata_ipfs: String) -> Result<()> {
+        let clock = Clock::get()?;
+        self.manufacturer.require_active(clock.unix_timestamp)?;
+
+        let previous_ipfs = self.product_batch.metadata_ipfs.clone();
+        self.product_batch
+            .update_metadata(metadata_ipfs.clone(), clock.unix_timestamp)?;
+
+        emit!(BatchMetadataUpdated {
+            batch_id,
+            version: self.product_batch.metadata_version,
+            previous_ipfs,
+            metadata_ipfs,
+            updated_by: self.signer.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct BatchMetadataUpdated {
+    pub batch_id: [u8; 32],
+    pub version: u16,
+    pub previous_ipfs: Option<String>,
+    pub metadata_ipfs: String,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+use crate::consts::*;
+use crate::state::*;
+
+/// Read only, meant to be simulated by clients
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct ViewBatchStatus<'info> {
+    #[account(
+        seeds = [BATCH, product_batch.manufacturer.as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+}
+
+impl<'info> ViewBatchStatus<'info> {
+    pub fn next_states(&self, _batch_id: [u8; 32]) -> Result<Vec<ProductStatus>> {
+        Ok(self.product_batch.status.next_states().to_vec())
+    }
+}
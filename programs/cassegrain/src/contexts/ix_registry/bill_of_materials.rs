@@ -0,0 +1,220 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::consts::*;
+use crate::helpers::init_pda_account;
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct DeclareBillOfMaterials<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// The assembled batch
+    #[account(
+        mut,
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+
+    #[account(
+        init,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR + BillOfMaterials::INIT_SPACE,
+        seeds = [BOM, product_batch.key().as_ref()],
+        bump,
+    )]
+    pub bill_of_materials: Account<'info, BillOfMaterials>,
+
+    #[account(
+        seeds = [CONFIG],
+        bump,
+        constraint = !cassegrain_config.is_paused
+            @ CassegrainError::ProgramPaused,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    #[account(
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key()
+            @ CassegrainError::Unauthorized,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// A batch only reaches `Recalled` through a `Recalled` update logged with
+/// `event_log` on the rollup, so the component has to be committed back
+/// to the base layer before its recall can be flagged here.
+#[derive(Accounts)]
+pub struct FlagComponentRecall<'info> {
+    #[account(
+        mut,
+        seeds = [BOM, bill_of_materials.assembly.as_ref()],
+        bump = bill_of_materials.bump,
+    )]
+    pub bill_of_materials: Account<'info, BillOfMaterials>,
+
+    #[account(
+        seeds = [BATCH, component_batch.manufacturer.as_ref(), component_batch.batch_id.as_ref()],
+        bump,
+        constraint = component_batch.status == ProductStatus::Recalled
+            @ CassegrainError::InvalidProductStatus,
+    )]
+    pub component_batch: Account<'info, ProductBatch>,
+}
+
+impl<'info> DeclareBillOfMaterials<'info> {
+    /// Remaining accounts are, per component, its batch, the signer's `BatchHandler`
+    /// grant when the component belongs to another owner, then its usage PDA.
+    /// Each component's quantity is drawn from its remaining quantity.
+    pub fn declare(
+        &mut self,
+        batch_id: [u8; 32],
+        quantities: Vec<u64>,
+        remaining: &'info [AccountInfo<'info>],
+        bumps: DeclareBillOfMaterialsBumps,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        self.manufacturer.require_active(clock.unix_timestamp)?;
+        require!(
+            !quantities.is_empty() && quantities.len() <= MAX_BOM_COMPONENTS,
+            CassegrainError::InvalidBillOfMaterials
+        );
+
+        let signer_key = self.signer.key();
+        let assembly = self.product_batch.key();
+        let bom_key = self.bill_of_materials.key();
+        let mut components: Vec<BomComponent> = Vec::with_capacity(quantities.len());
+        let mut accounts = remaining.iter();
+
+        for quantity in quantities.iter() {
+            let component_info = accounts.next().ok_or(CassegrainError::InvalidBillOfMaterials)?;
+            require!(*quantity > 0, CassegrainError::InvalidBillOfMaterials);
+            require!(component_info.is_writable, CassegrainError::InvalidBillOfMaterials);
+
+            let mut component = Account::<ProductBatch>::try_from(component_info)?;
+            require_keys_eq!(
+                component.address()?,
+                component_info.key(),
+                CassegrainError::InvalidBatchId
+            );
+            require!(
+                component_info.key() != assembly
+                    && components.iter().all(|c| c.batch != component_info.key()),
+                CassegrainError::InvalidBillOfMaterials
+            );
+            require!(
+                component.status != ProductStatus::Recalled,
+                CassegrainError::ComponentRecalled
+            );
+
+            // Drawing from another owner's batch needs their grant
+            if component.manufacturer != signer_key {
+                let grant_info = accounts.next().ok_or(CassegrainError::Unauthorized)?;
+                let grant = Account::<BatchHandler>::try_from(grant_info)?;
+                require!(grant.batch == component_info.key(), CassegrainError::Unauthorized);
+                component.authorize_actor(&signer_key, Some(&grant))?;
+            }
+            component.consume_quantity(*quantity)?;
+            component.exit(&crate::ID)?;
+
+            let usage_info = accounts.next().ok_or(CassegrainError::InvalidBillOfMaterials)?;
+            let (usage_key, usage_bump) = Pubkey::find_program_address(
+                &[COMPONENT_USAGE, component_info.key.as_ref(), assembly.as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(usage_info.key(), usage_key, CassegrainError::InvalidBillOfMaterials);
+
+            init_pda_account(
+                &self.signer.to_account_info(),
+                usage_info,
+                &self.system_program.to_account_info(),
+                &[COMPONENT_USAGE, component_info.key.as_ref(), assembly.as_ref(), &[usage_bump]],
+                ANCHOR_DISCRIMINATOR + ComponentUsage::INIT_SPACE,
+                &ComponentUsage {
+                    component: component_info.key(),
+                    assembly,
+                    bill_of_materials: bom_key,
+                    quantity: *quantity,
+                    bump: usage_bump,
+                },
+            )?;
+
+            components.push(BomComponent {
+                batch: component_info.key(),
+                quantity: *quantity,
+                recalled: false,
+            });
+        }
+        require!(accounts.next().is_none(), CassegrainError::InvalidBillOfMaterials);
+
+        emit!(BillOfMaterialsDeclared {
+            batch_id,
+            assembly,
+            components: components.iter().map(|c| c.batch).collect(),
+            quantities,
+            timestamp: clock.unix_timestamp,
+        });
+
+        self.product_batch.bom_components = components.len() as u8;
+        self.bill_of_materials.set_inner(BillOfMaterials {
+            assembly,
+            assembly_batch_id: batch_id,
+            manufacturer: self.signer.key(),
+            components,
+            recalled_components: 0,
+            created_at: clock.unix_timestamp,
+            bump: bumps.bill_of_materials,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> FlagComponentRecall<'info> {
+    /// Permissionless, anyone can propagate a component recall to an assembly
+    pub fn flag(&mut self) -> Result<()> {
+        let component_key = self.component_batch.key();
+        let entry = self
+            .bill_of_materials
+            .components
+            .iter_mut()
+            .find(|c| c.batch == component_key)
+            .ok_or(CassegrainError::InvalidBillOfMaterials)?;
+        require!(!entry.recalled, CassegrainError::InvalidBillOfMaterials);
+        entry.recalled = true;
+        self.bill_of_materials.recalled_components += 1;
+
+        emit!(ComponentRecallFlagged {
+            assembly: self.bill_of_materials.assembly,
+            assembly_batch_id: self.bill_of_materials.assembly_batch_id,
+            component: component_key,
+            component_batch_id: self.component_batch.batch_id,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct BillOfMaterialsDeclared {
+    pub batch_id: [u8; 32],
+    pub assembly: Pubkey,
+    pub components: Vec<Pubkey>,
+    pub quantities: Vec<u64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ComponentRecallFlagged {
+    pub assembly: Pubkey,
+    pub assembly_batch_id: [u8; 32],
+    pub component: Pubkey,
+    pub component_batch_id: [u8; 32],
+    pub timestamp: i64,
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::consts::*;
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct CloseProductBatch<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        close = signer,
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+        constraint = product_batch.manufacturer == signer.key() 
+            @ CassegrainError::Unauthorized,
+        constraint = product_batch.total_events == 0 
+            @ CassegrainError::BatchHasEvents,
+        constraint = product_batch.rollup_records == 0 
+            @ CassegrainError::BatchHasEvents,
+        constraint = product_batch.parent_batches.is_empty() 
+            && product_batch.derived_batches == 0 
+            @ CassegrainError::BatchHasLineage,
+        constraint = product_batch.units_registered == 0 
+            && product_batch.unit_leaf_count == 0 
+            @ CassegrainError::BatchHasUnits,
+        constraint = product_batch.handlers_granted == 0 
+            && product_batch.bom_components == 0 
+            @ CassegrainError::BatchAccountsOpen,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+
+    #[account(
+        mut,
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+}
+
+impl<'info> CloseProductBatch<'info> {
+    pub fn close(&mut self, batch_id: [u8; 32]) -> Result<()> {
+        self.manufacturer.close_batch()?;
+
+        emit!(ProductBatchClosed {
+            batch_id,
+            manufacturer: self.signer.key(),
+            active_batches: self.manufacturer.active_batches,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct ProductBatchClosed {
+    pub batch_id: [u8; 32],
+    pub manufacturer: Pubkey,
+    pub active_batches: u32,
+    pub timestamp: i64,
+}
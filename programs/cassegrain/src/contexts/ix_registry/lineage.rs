@@ -0,0 +1,330 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::consts::*;
+use crate::helpers::{charge_registration_fee, init_pda_account, require_batch_id_unused};
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct SplitBatch<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// The batch being split, marked `Repackaged` afterwards
+    #[account(
+        mut,
+        seeds = [BATCH, product_batch.manufacturer.as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+
+    /// Required when the signer is not the batch manufacturer
+    #[account(
+        seeds = [HANDLER, product_batch.key().as_ref(), signer.key().as_ref()],
+        bump = batch_handler.bump,
+    )]
+    pub batch_handler: Option<Account<'info, BatchHandler>>,
+
+    #[account(
+        seeds = [CONFIG],
+        bump,
+        constraint = !cassegrain_config.is_paused 
+            @ CassegrainError::ProgramPaused,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    /// CHECK: Only receives lamports, address pinned by the config
+    #[account(
+        mut,
+        address = cassegrain_config.fee_treasury 
+            @ CassegrainError::InvalidFeeTreasury,
+    )]
+    pub fee_treasury: UncheckedAccount<'info>,
+
+    /// Profile of the signer, the child batches are registered under it
+    #[account(
+        mut,
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key() 
+            @ CassegrainError::Unauthorized,
+        constraint = manufacturer.is_verified 
+            @ CassegrainError::ManufacturerNotVerified,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct MergeBatches<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// The merged batch, registered under the signer
+    #[account(
+        init,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR + ProductBatch::INIT_SPACE,
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+
+    /// CHECK: Checked by `require_batch_id_unused`, an archived batch id can not be reused
+    pub batch_archive: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG],
+        bump,
+        constraint = !cassegrain_config.is_paused 
+            @ CassegrainError::ProgramPaused,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    /// CHECK: Only receives lamports, address pinned by the config
+    #[account(
+        mut,
+        address = cassegrain_config.fee_treasury 
+            @ CassegrainError::InvalidFeeTreasury,
+    )]
+    pub fee_treasury: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key() 
+            @ CassegrainError::Unauthorized,
+        constraint = manufacturer.is_verified 
+            @ CassegrainError::ManufacturerNotVerified,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SplitBatch<'info> {
+    pub fn split(
+        &mut self,
+        batch_id: [u8; 32],
+        children: Vec<ChildBatch>,
+        child_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        self.manufacturer.require_active(clock.unix_timestamp)?;
+        self.product_batch
+            .authorize_actor(&self.signer.key(), self.batch_handler.as_deref())?;
+
+        require!(
+            !children.is_empty() && children.len() * 2 == child_accounts.len(),
+            CassegrainError::QuantityMismatch
+        );
+        let mut total: u64 = 0;
+        for child in &children {
+            require!(child.batch_size > 0, CassegrainError::InvalidBatchSize);
+            total = total
+                .checked_add(child.batch_size)
+                .ok_or(CassegrainError::MathOverflow)?;
+        }
+        require!(
+            total == self.product_batch.remaining_quantity,
+            CassegrainError::QuantityMismatch
+        );
+
+        let parent_key = self.product_batch.key();
+        let parent = &self.product_batch;
+        let signer_key = self.signer.key();
+        let mut child_keys = Vec::with_capacity(children.len());
+
+        for (child, pair) in children.iter().zip(child_accounts.chunks(2)) {
+            let (account, child_archive) = (&pair[0], &pair[1]);
+            require_batch_id_unused(child_archive, &signer_key, &child.batch_id)?;
+            let (expected, bump) = Pubkey::find_program_address(
+                &[BATCH, signer_key.as_ref(), child.batch_id.as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(account.key(), expected, CassegrainError::InvalidBatchId);
+            require!(account.data_is_empty(), CassegrainError::ProductAlreadyExists);
+
+            self.manufacturer.open_batch(&self.cassegrain_config)?;
+            charge_registration_fee(
+                &self.signer.to_account_info(),
+                &self.fee_treasury.to_account_info(),
+                &self.system_program.to_account_info(),
+                &self.cassegrain_config,
+            )?;
+
+            let mut child_batch = ProductBatch::new(
+                child.batch_id,
+                &self.manufacturer,
+                parent.category,
+                parent.metadata_ipfs.clone(),
+                child.batch_size,
+                parent.unit_of_measure,
+                clock.unix_timestamp,
+                bump,
+            );
+            child_batch.authenticity_verified = parent.authenticity_verified;
+            child_batch.parent_batches = vec![parent_key];
+
+            init_pda_account(
+                &self.signer.to_account_info(),
+                account,
+                &self.system_program.to_account_info(),
+                &[BATCH, signer_key.as_ref(), child.batch_id.as_ref(), &[bump]],
+                ANCHOR_DISCRIMINATOR + ProductBatch::INIT_SPACE,
+                &child_batch,
+            )?;
+            child_keys.push(expected);
+        }
+
+        self.product_batch
+            .mark_repackaged(children.len() as u16, clock.unix_timestamp)?;
+
+        emit!(BatchSplit {
+            batch_id,
+            parent: parent_key,
+            children: child_keys,
+            quantities: children.iter().map(|child| child.batch_size).collect(),
+            split_by: signer_key,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> MergeBatches<'info> {
+    /// Remaining accounts are the input batches, each one not owned by the
+    /// signer is followed by the signer's `BatchHandler` grant for it and
+    /// its owner's profile.
+    pub fn merge(
+        &mut self,
+        batch_id: [u8; 32],
+        metadata_ipfs: Option<String>,
+        remaining: &'info [AccountInfo<'info>],
+        bumps: MergeBatchesBumps,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let signer_key = self.signer.key();
+        self.manufacturer.require_active(clock.unix_timestamp)?;
+        require_batch_id_unused(&self.batch_archive, &signer_key, &batch_id)?;
+        if let Some(ref ipfs) = metadata_ipfs {
+            require!(ipfs.len() <= 32, CassegrainError::InvalidIPFSHash);
+        }
+
+        let mut inputs: Vec<Account<'info, ProductBatch>> = Vec::new();
+        let mut accounts = remaining.iter();
+        while let Some(info) = accounts.next() {
+            require!(info.is_writable, CassegrainError::IncompatibleBatches);
+            let input = Account::<ProductBatch>::try_from(info)?;
+            require_keys_eq!(input.address()?, info.key(), CassegrainError::InvalidBatchId);
+            require!(
+                inputs.iter().all(|other| other.key() != info.key()),
+                CassegrainError::IncompatibleBatches
+            );
+
+            if input.manufacturer != signer_key {
+                let grant_info = accounts.next().ok_or(CassegrainError::Unauthorized)?;
+                let grant = Account::<BatchHandler>::try_from(grant_info)?;
+                require!(grant.batch == info.key(), CassegrainError::Unauthorized);
+                input.authorize_actor(&signer_key, Some(&grant))?;
+
+                // A suspended owner's batches can't be drawn into a merge
+                let owner_info = accounts.next().ok_or(CassegrainError::Unauthorized)?;
+                let owner = Account::<ManufacturerProfile>::try_from(owner_info)?;
+                let (owner_key, _) = Pubkey::find_program_address(
+                    &[MANUFACTURER, input.manufacturer.as_ref()],
+                    &crate::ID,
+                );
+                require_keys_eq!(owner_info.key(), owner_key, CassegrainError::Unauthorized);
+                owner.require_active(clock.unix_timestamp)?;
+            }
+            inputs.push(input);
+        }
+
+        require!(
+            inputs.len() >= 2 && inputs.len() <= MAX_PARENT_BATCHES,
+            CassegrainError::IncompatibleBatches
+        );
+        let category = inputs[0].category;
+        let unit_of_measure = inputs[0].unit_of_measure;
+        require!(
+            inputs.iter().all(|input| {
+                input.category == category && input.unit_of_measure == unit_of_measure
+            }),
+            CassegrainError::IncompatibleBatches
+        );
+
+        let quantities: Vec<u64> = inputs.iter().map(|input| input.remaining_quantity).collect();
+        let total = quantities
+            .iter()
+            .try_fold(0u64, |total, quantity| total.checked_add(*quantity))
+            .ok_or(CassegrainError::MathOverflow)?;
+        require!(
+            total > 0 && total <= unit_of_measure.scale_limit(self.cassegrain_config.max_batch_size),
+            CassegrainError::InvalidBatchSize
+        );
+
+        self.manufacturer.open_batch(&self.cassegrain_config)?;
+        charge_registration_fee(
+            &self.signer.to_account_info(),
+            &self.fee_treasury.to_account_info(),
+            &self.system_program.to_account_info(),
+            &self.cassegrain_config,
+        )?;
+
+        let mut merged = ProductBatch::new(
+            batch_id,
+            &self.manufacturer,
+            category,
+            metadata_ipfs,
+            total,
+            unit_of_measure,
+            clock.unix_timestamp,
+            bumps.product_batch,
+        );
+        merged.authenticity_verified = inputs.iter().all(|input| input.authenticity_verified);
+        merged.parent_batches = inputs.iter().map(|input| input.key()).collect();
+
+        for input in inputs.iter_mut() {
+            input.mark_repackaged(1, clock.unix_timestamp)?;
+            input.exit(&crate::ID)?;
+        }
+
+        emit!(BatchesMerged {
+            batch_id,
+            merged_batch: self.product_batch.key(),
+            inputs: merged.parent_batches.clone(),
+            quantities,
+            merged_by: signer_key,
+            timestamp: clock.unix_timestamp,
+        });
+
+        self.product_batch.set_inner(merged);
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct BatchSplit {
+    pub batch_id: [u8; 32],
+    pub parent: Pubkey,
+    pub children: Vec<Pubkey>,
+    pub quantities: Vec<u64>,
+    pub split_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchesMerged {
+    pub batch_id: [u8; 32],
+    pub merged_batch: Pubkey,
+    pub inputs: Vec<Pubkey>,
+    pub quantities: Vec<u64>,
+    pub merged_by: Pubkey,
+    pub timestamp: i64,
+}
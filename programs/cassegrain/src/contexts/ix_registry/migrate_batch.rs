@@ -0,0 +1,163 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::state::*;
+use crate::error::*;
+use crate::consts::*;
+use crate::helpers::{close_raw_account, require_batch_id_unused};
+
+/// Layout of `ProductBatch` when it was still seeded by `[BATCH, batch_id]`
+#[derive(AnchorDeserialize)]
+pub struct LegacyProductBatch {
+    pub batch_id: [u8; 32],
+    pub manufacturer_name: String,
+    pub status: ProductStatus,
+    pub created_at: i64,
+    pub last_updated: i64,
+    pub metadata_ipfs: Option<String>,
+    pub authenticity_verified: bool,
+    pub category: ProductCategory,
+    pub manufacturer: Pubkey,
+    pub event_account: Option<Pubkey>,
+    pub total_events: u32,
+    pub batch_size: u8,
+    pub bump: u8,
+}
+
+/// Moves a batch registered under the old `[BATCH, batch_id]` seeds to
+/// `[BATCH, manufacturer, batch_id]`. The manufacturer signs, the data is
+/// copied over and the old account is closed with its rent refunded.
+/// Batches still delegated to the rollup have to be undelegated first, and a
+/// profile still in the old layout has to go through `migrate_profile`.
+///
+/// Legacy events are not carried over: they predate the hash chain, so the
+/// migrated batch starts a fresh chain at sequence 0 with no head, tail or
+/// digest. They stay readable through their `batch_id` but are not covered
+/// by `verify_history`, are not folded into an archive and no longer
+/// count against the event limit. `ProductBatchMigrated::legacy_events`
+/// records how many were left behind.
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct MigrateProductBatch<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: Old batch account, decoded by hand since its layout predates the current one
+    #[account(
+        mut,
+        seeds = [BATCH, batch_id.as_ref()],
+        bump,
+        owner = crate::ID,
+    )]
+    pub legacy_batch: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR + ProductBatch::INIT_SPACE,
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+
+    /// CHECK: Checked by `require_batch_id_unused`, an archived batch id can not be reused
+    pub batch_archive: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateProductBatch<'info> {
+    pub fn migrate(&mut self, batch_id: [u8; 32], bumps: MigrateProductBatchBumps) -> Result<()> {
+        require_batch_id_unused(&self.batch_archive, &self.signer.key(), &batch_id)?;
+        let legacy = {
+            let data = self.legacy_batch.try_borrow_data()?;
+            require!(
+                data.len() > ANCHOR_DISCRIMINATOR
+                    && &data[..ANCHOR_DISCRIMINATOR] == ProductBatch::DISCRIMINATOR,
+                CassegrainError::InvalidLegacyAccount
+            );
+            LegacyProductBatch::deserialize(&mut &data[ANCHOR_DISCRIMINATOR..])?
+        };
+        require!(legacy.batch_id == batch_id, CassegrainError::InvalidBatchId);
+        require!(
+            legacy.manufacturer == self.signer.key(),
+            CassegrainError::Unauthorized
+        );
+
+        self.product_batch.set_inner(ProductBatch {
+            batch_id,
+            manufacturer_name: legacy.manufacturer_name,
+            status: legacy.status,
+            order_status: OrderStatus::Pending,
+            created_at: legacy.created_at,
+            last_updated: legacy.last_updated,
+            metadata_ipfs: legacy.metadata_ipfs,
+            authenticity_verified: legacy.authenticity_verified,
+            category: legacy.category,
+            manufacturer: legacy.manufacturer,
+            event_account: legacy.event_account,
+            head_event: None,
+            tail_event: None,
+            total_events: 0,
+            rollup_records: 0,
+            history_digest: [0u8; 32],
+            max_events_override: None,
+            units_registered: 0,
+            unit_merkle_root: [0u8; 32],
+            unit_leaf_count: 0,
+            parent_batches: Vec::new(),
+            derived_batches: 0,
+            handlers_granted: 0,
+            bom_components: 0,
+            batch_size: legacy.batch_size as u64,
+            unit_of_measure: UnitOfMeasure::Pieces,
+            remaining_quantity: legacy.batch_size as u64,
+            consumed_quantity: 0,
+            metadata_version: 0,
+            metadata_history: Vec::new(),
+            bump: bumps.product_batch,
+        });
+
+        // Legacy batches predate the counter, count them without applying the limit
+        self.manufacturer.active_batches = self
+            .manufacturer
+            .active_batches
+            .checked_add(1)
+            .ok_or(CassegrainError::MathOverflow)?;
+
+        close_raw_account(
+            &self.legacy_batch.to_account_info(),
+            &self.signer.to_account_info(),
+        )?;
+
+        emit!(ProductBatchMigrated {
+            batch_id,
+            legacy_batch: self.legacy_batch.key(),
+            product_batch: self.product_batch.key(),
+            manufacturer: self.signer.key(),
+            legacy_events: legacy.total_events,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct ProductBatchMigrated {
+    pub batch_id: [u8; 32],
+    pub legacy_batch: Pubkey,
+    pub product_batch: Pubkey,
+    pub manufacturer: Pubkey,
+    /// Events logged before the migration, outside the new batch's chain
+    pub legacy_events: u32,
+    pub timestamp: i64,
+}
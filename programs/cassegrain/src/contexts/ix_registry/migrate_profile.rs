@@ -0,0 +1,154 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+use crate::state::*;
+use crate::error::*;
+use crate::consts::*;
+
+/// Layout of `ManufacturerProfile` before verification, batch counting and
+/// suspensions were added
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyManufacturerProfile {
+    pub company_name: String,
+    pub business_type: BusinessType,
+    pub owner: Pubkey,
+    pub certifications: String,
+    pub is_verified: bool,
+    pub bump: u8,
+}
+
+impl LegacyManufacturerProfile {
+    /// Legacy profiles verified themselves on registration, so the migrated
+    /// profile starts unverified and has to go through a verifier
+    pub fn into_current(self) -> ManufacturerProfile {
+        ManufacturerProfile {
+            company_name: self.company_name,
+            business_type: self.business_type,
+            owner: self.owner,
+            certifications: self.certifications,
+            is_verified: false,
+            verified_by: None,
+            verified_at: None,
+            verification_cid: None,
+            // Legacy batches are counted as they go through `migrate_product_batch`
+            active_batches: 0,
+            suspension: None,
+            bump: self.bump,
+        }
+    }
+}
+
+/// Grows a profile registered with the old layout to the current one in
+/// place. Has to run before the owner can use any instruction that loads
+/// the profile, `migrate_product_batch` included.
+#[derive(Accounts)]
+pub struct MigrateProfile<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: Profile in the old layout, decoded by hand since it predates verification
+    #[account(
+        mut,
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        owner = crate::ID,
+    )]
+    pub manufacturer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateProfile<'info> {
+    pub fn migrate(&mut self) -> Result<()> {
+        let space = ANCHOR_DISCRIMINATOR + ManufacturerProfile::INIT_SPACE;
+        let legacy = {
+            let data = self.manufacturer.try_borrow_data()?;
+            require!(
+                data.len() > ANCHOR_DISCRIMINATOR
+                    && data.len() < space
+                    && &data[..ANCHOR_DISCRIMINATOR] == ManufacturerProfile::DISCRIMINATOR,
+                CassegrainError::InvalidLegacyAccount
+            );
+            LegacyManufacturerProfile::deserialize(&mut &data[ANCHOR_DISCRIMINATOR..])?
+        };
+        require!(
+            legacy.owner == self.signer.key(),
+            CassegrainError::Unauthorized
+        );
+
+        // The owner pays for the extra space
+        let manufacturer = self.manufacturer.to_account_info();
+        let top_up = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(manufacturer.lamports());
+        if top_up > 0 {
+            transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.signer.to_account_info(),
+                        to: manufacturer.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        manufacturer.resize(space)?;
+
+        let profile = legacy.into_current();
+        let mut data = manufacturer.try_borrow_mut_data()?;
+        profile.try_serialize(&mut &mut data[..])?;
+
+        emit!(ProfileMigrated {
+            manufacturer: self.signer.key(),
+            profile: manufacturer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct ProfileMigrated {
+    pub manufacturer: Pubkey,
+    pub profile: Pubkey,
+    pub timestamp: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn baseline_profile_round_trips() {
+        let owner = Pubkey::new_unique();
+        let legacy = LegacyManufacturerProfile {
+            company_name: "Acme".to_string(),
+            business_type: BusinessType::Distributor,
+            owner,
+            certifications: "ISO 9001".to_string(),
+            is_verified: true,
+            bump: 254,
+        };
+
+        // Account data as the baseline `register_manufacturer` left it
+        let mut data = ManufacturerProfile::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+        data.resize(ANCHOR_DISCRIMINATOR + 107, 0);
+
+        let decoded = LegacyManufacturerProfile::deserialize(&mut &data[ANCHOR_DISCRIMINATOR..]).unwrap();
+        let mut migrated = vec![0u8; ANCHOR_DISCRIMINATOR + ManufacturerProfile::INIT_SPACE];
+        decoded.into_current().try_serialize(&mut &mut migrated[..]).unwrap();
+
+        let profile = ManufacturerProfile::try_deserialize(&mut &migrated[..]).unwrap();
+        assert_eq!(profile.company_name, "Acme");
+        assert_eq!(profile.business_type, BusinessType::Distributor);
+        assert_eq!(profile.owner, owner);
+        assert_eq!(profile.certifications, "ISO 9001");
+        assert_eq!(profile.bump, 254);
+        assert!(!profile.is_verified);
+        assert_eq!(profile.active_batches, 0);
+        assert!(profile.suspension.is_none());
+    }
+}
@@ -2,4 +2,40 @@ pub mod profile;
 pub use profile::*;
 
 pub mod register_product;
-pub use register_product::*;
\ No newline at end of file
+pub use register_product::*;
+
+pub mod close_product;
+pub use close_product::*;
+
+pub mod verification;
+pub use verification::*;
+
+pub mod batch_access;
+pub use batch_access::*;
+
+pub mod migrate_batch;
+pub use migrate_batch::*;
+
+pub mod migrate_profile;
+pub use migrate_profile::*;
+
+pub mod register_unit;
+pub use register_unit::*;
+
+pub mod unit_tree;
+pub use unit_tree::*;
+
+pub mod lineage;
+pub use lineage::*;
+
+pub mod bill_of_materials;
+pub use bill_of_materials::*;
+
+pub mod batch_metadata;
+pub use batch_metadata::*;
+
+pub mod archive_batch;
+pub use archive_batch::*;
+
+pub mod batch_status;
+pub use batch_status::*;
\ No newline at end of file
@@ -8,13 +8,10 @@ pub struct RegisterProfile<'info> {
   #[account(mut)]
   pub signer : Signer<'info>,
 
-  /// CHECK : The authority of this program
-  pub authority: UncheckedAccount<'info>,
    #[account(
     mut,
-    seeds = [CONFIG, authority.key().as_ref()],
+    seeds = [CONFIG],
     bump,
-    constraint = cassegrain_config.authority == authority.key() @CassegrainError::Unauthorized,
     constraint = !cassegrain_config.is_paused @CassegrainError::ProgramPaused  
   )]
   pub cassegrain_config: Account<'info, CassegrainConfig>,
@@ -31,6 +28,35 @@ pub struct RegisterProfile<'info> {
   pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateProfile<'info> {
+  pub signer: Signer<'info>,
+
+  #[account(
+    mut,
+    seeds = [MANUFACTURER, signer.key().as_ref()],
+    bump,
+    constraint = manufacturer.owner == signer.key() @CassegrainError::Unauthorized
+  )]
+  pub manufacturer: Account<'info, ManufacturerProfile>,
+}
+
+#[derive(Accounts)]
+pub struct CloseProfile<'info> {
+  #[account(mut)]
+  pub signer: Signer<'info>,
+
+  #[account(
+    mut,
+    close = signer,
+    seeds = [MANUFACTURER, signer.key().as_ref()],
+    bump,
+    constraint = manufacturer.owner == signer.key() @CassegrainError::Unauthorized,
+    constraint = manufacturer.active_batches == 0 @CassegrainError::ManufacturerHasLiveBatches
+  )]
+  pub manufacturer: Account<'info, ManufacturerProfile>,
+}
+
 impl <'info> RegisterProfile<'info> {
   pub fn register(
     &mut self,
@@ -39,6 +65,7 @@ impl <'info> RegisterProfile<'info> {
     certifications: String,
     bumps: RegisterProfileBumps
   ) -> Result<()> {
+    ManufacturerProfile::validate_identity(&company_name, &certifications)?;
 
     self.manufacturer.set_inner(
       ManufacturerProfile { 
@@ -46,10 +73,83 @@ impl <'info> RegisterProfile<'info> {
         business_type, 
         owner: self.signer.key(),
         certifications, 
-        is_verified: true,  // set to false and set a way to verify later
+        is_verified: false,
+        verified_by: None,
+        verified_at: None,
+        verification_cid: None,
+        active_batches: 0,
+        suspension: None,
         bump: bumps.manufacturer
       });
 
     Ok(())
   }
-}
\ No newline at end of file
+}
+
+impl <'info> UpdateProfile<'info> {
+  pub fn update(
+    &mut self,
+    company_name: Option<String>,
+    business_type: Option<BusinessType>,
+    certifications: Option<String>,
+  ) -> Result<()> {
+    let profile = &mut self.manufacturer;
+
+    let company_name = company_name.unwrap_or_else(|| profile.company_name.clone());
+    let business_type = business_type.unwrap_or(profile.business_type);
+    let certifications = certifications.unwrap_or_else(|| profile.certifications.clone());
+    ManufacturerProfile::validate_identity(&company_name, &certifications)?;
+
+    // Any change to the identity invalidates what the verifier signed off on
+    let identity_changed = company_name != profile.company_name
+      || business_type != profile.business_type
+      || certifications != profile.certifications;
+    let verification_reset = identity_changed && profile.is_verified;
+    if verification_reset {
+      profile.clear_verification();
+    }
+
+    profile.company_name = company_name.clone();
+    profile.business_type = business_type;
+    profile.certifications = certifications;
+
+    emit!(ProfileUpdated {
+      manufacturer: profile.owner,
+      company_name,
+      business_type,
+      verification_reset,
+      timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+  }
+}
+
+impl <'info> CloseProfile<'info> {
+  pub fn close(&mut self) -> Result<()> {
+    // Closing must not be a way to shed a suspension
+    self.manufacturer.require_active(Clock::get()?.unix_timestamp)?;
+
+    emit!(ProfileClosed {
+      manufacturer: self.manufacturer.owner,
+      timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+  }
+}
+
+#[event]
+pub struct ProfileUpdated {
+  pub manufacturer: Pubkey,
+  pub company_name: String,
+  pub business_type: BusinessType,
+  pub verification_reset: bool,
+  pub timestamp: i64,
+}
+
+#[event]
+pub struct ProfileClosed {
+  pub manufacturer: Pubkey,
+  pub timestamp: i64,
+}
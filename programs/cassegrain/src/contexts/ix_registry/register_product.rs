@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::error::*;
 use crate::consts::*;
+use crate::helpers::{charge_registration_fee, require_batch_id_unused};
 
 #[derive(Accounts)]
 #[instruction(batch_id: [u8; 32])]
@@ -9,29 +10,35 @@ pub struct RegisterProduct<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
     
-    /// CHECK: The authority of this program
-    pub authority: UncheckedAccount<'info>,
-    
     #[account(
         init_if_needed,
         payer = signer,
         space = ANCHOR_DISCRIMINATOR + ProductBatch::INIT_SPACE,
-        seeds = [BATCH, batch_id.as_ref()],
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
         bump,
     )]
     pub product_batch: Account<'info, ProductBatch>,
+
+    /// CHECK: Checked by `require_batch_id_unused`, an archived batch id can not be reused
+    pub batch_archive: UncheckedAccount<'info>,
     
     #[account(
         mut,
-        seeds = [CONFIG, authority.key().as_ref()],
+        seeds = [CONFIG],
         bump,
-        constraint = cassegrain_config.authority == authority.key() 
-            @ CassegrainError::Unauthorized,
         constraint = !cassegrain_config.is_paused 
             @ CassegrainError::ProgramPaused,
     )]
     pub cassegrain_config: Account<'info, CassegrainConfig>,
 
+    /// CHECK: Only receives lamports, address pinned by the config
+    #[account(
+        mut,
+        address = cassegrain_config.fee_treasury 
+            @ CassegrainError::InvalidFeeTreasury,
+    )]
+    pub fee_treasury: UncheckedAccount<'info>,
+
     #[account(
         mut,
         seeds = [MANUFACTURER, signer.key().as_ref()],
@@ -52,38 +59,45 @@ impl<'info> RegisterProduct<'info> {
         batch_id: [u8; 32],
         metadata_ipfs: Option<String>,
         category: ProductCategory,
-        batch_size: u8,
+        batch_size: u64,
+        unit_of_measure: UnitOfMeasure,
         bumps: RegisterProductBumps,
     ) -> Result<()> {
         let clock = Clock::get()?;
         let config = &self.cassegrain_config;
         
         // Validation checks
+        self.manufacturer.require_active(clock.unix_timestamp)?;
         require!(
-            batch_size > 0 && batch_size <= config.max_batch_size,
+            batch_size > 0 && batch_size <= unit_of_measure.scale_limit(config.max_batch_size),
             CassegrainError::InvalidBatchSize
         );
-        
 
-        
-      
-        if self.product_batch.batch_size == 0 {
-            self.product_batch.set_inner(ProductBatch {
-                batch_id,
-                manufacturer_name: self.manufacturer.company_name.clone(),
-                status: ProductStatus::Created,
-                created_at: clock.unix_timestamp,
-                last_updated: clock.unix_timestamp,
-                metadata_ipfs: metadata_ipfs.clone(),
-                authenticity_verified: false,
-                category,
-                event_account: None,
-                manufacturer: self.manufacturer.owner,
-                total_events: 0,
-                batch_size,
-                bump: bumps.product_batch,
-            });
-        }
+        // init_if_needed hands us the existing account on a duplicate id
+        require!(
+            self.product_batch.created_at == 0,
+            CassegrainError::ProductAlreadyExists
+        );
+        require_batch_id_unused(&self.batch_archive, &self.signer.key(), &batch_id)?;
+
+        self.manufacturer.open_batch(config)?;
+        charge_registration_fee(
+            &self.signer.to_account_info(),
+            &self.fee_treasury.to_account_info(),
+            &self.system_program.to_account_info(),
+            config,
+        )?;
+
+        self.product_batch.set_inner(ProductBatch::new(
+            batch_id,
+            &self.manufacturer,
+            category,
+            metadata_ipfs,
+            batch_size,
+            unit_of_measure,
+            clock.unix_timestamp,
+            bumps.product_batch,
+        ));
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,237 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::consts::*;
+use crate::helpers::init_pda_account;
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32], serial_number: [u8; 32])]
+pub struct RegisterProductUnit<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+
+    #[account(
+        init,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR + ProductUnit::INIT_SPACE,
+        seeds = [UNIT, product_batch.key().as_ref(), serial_number.as_ref()],
+        bump,
+    )]
+    pub product_unit: Account<'info, ProductUnit>,
+
+    #[account(
+        seeds = [CONFIG],
+        bump,
+        constraint = !cassegrain_config.is_paused 
+            @ CassegrainError::ProgramPaused,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    #[account(
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct RegisterProductUnits<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+
+    #[account(
+        seeds = [CONFIG],
+        bump,
+        constraint = !cassegrain_config.is_paused 
+            @ CassegrainError::ProgramPaused,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    #[account(
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferProductUnit<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [UNIT, product_unit.batch.as_ref(), product_unit.serial_number.as_ref()],
+        bump = product_unit.bump,
+        constraint = product_unit.current_owner == owner.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub product_unit: Account<'info, ProductUnit>,
+}
+
+fn validate_unit_metadata(metadata_ipfs: &Option<String>) -> Result<()> {
+    if let Some(ipfs) = metadata_ipfs {
+        require!(ipfs.len() <= 32, CassegrainError::InvalidIPFSHash);
+    }
+    Ok(())
+}
+
+impl<'info> RegisterProductUnit<'info> {
+    pub fn register(
+        &mut self,
+        batch_id: [u8; 32],
+        serial_number: [u8; 32],
+        metadata_ipfs: Option<String>,
+        owner: Option<Pubkey>,
+        bumps: RegisterProductUnitBumps,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        self.manufacturer.require_active(clock.unix_timestamp)?;
+        validate_unit_metadata(&metadata_ipfs)?;
+
+        self.product_batch.add_units(1)?;
+        self.product_batch.last_updated = clock.unix_timestamp;
+
+        let current_owner = owner.unwrap_or(self.signer.key());
+        self.product_unit.set_inner(ProductUnit {
+            serial_number,
+            batch: self.product_batch.key(),
+            batch_id,
+            created_at: clock.unix_timestamp,
+            last_updated: clock.unix_timestamp,
+            metadata_ipfs,
+            current_owner,
+            bump: bumps.product_unit,
+        });
+
+        emit!(UnitRegistered {
+            batch_id,
+            serial_number,
+            unit: self.product_unit.key(),
+            owner: current_owner,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> RegisterProductUnits<'info> {
+    pub fn register(
+        &mut self,
+        batch_id: [u8; 32],
+        units: Vec<UnitRegistration>,
+        unit_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        self.manufacturer.require_active(clock.unix_timestamp)?;
+        require!(
+            !units.is_empty() && units.len() == unit_accounts.len(),
+            CassegrainError::InvalidUnit
+        );
+
+        self.product_batch.add_units(units.len() as u32)?;
+        self.product_batch.last_updated = clock.unix_timestamp;
+
+        let batch_key = self.product_batch.key();
+        for (unit, account) in units.into_iter().zip(unit_accounts) {
+            validate_unit_metadata(&unit.metadata_ipfs)?;
+
+            let (expected, bump) = Pubkey::find_program_address(
+                &[UNIT, batch_key.as_ref(), unit.serial_number.as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(account.key(), expected, CassegrainError::InvalidUnit);
+            require!(account.data_is_empty(), CassegrainError::UnitAlreadyExists);
+            let current_owner = unit.owner.unwrap_or(self.signer.key());
+
+            init_pda_account(
+                &self.signer.to_account_info(),
+                account,
+                &self.system_program.to_account_info(),
+                &[UNIT, batch_key.as_ref(), unit.serial_number.as_ref(), &[bump]],
+                ANCHOR_DISCRIMINATOR + ProductUnit::INIT_SPACE,
+                &ProductUnit {
+                    serial_number: unit.serial_number,
+                    batch: batch_key,
+                    batch_id,
+                    created_at: clock.unix_timestamp,
+                    last_updated: clock.unix_timestamp,
+                    metadata_ipfs: unit.metadata_ipfs,
+                    current_owner,
+                    bump,
+                },
+            )?;
+
+            emit!(UnitRegistered {
+                batch_id,
+                serial_number: unit.serial_number,
+                unit: expected,
+                owner: current_owner,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl<'info> TransferProductUnit<'info> {
+    pub fn transfer(&mut self, new_owner: Pubkey) -> Result<()> {
+        let clock = Clock::get()?;
+        let previous_owner = self.product_unit.current_owner;
+
+        self.product_unit.current_owner = new_owner;
+        self.product_unit.last_updated = clock.unix_timestamp;
+
+        emit!(UnitTransferred {
+            unit: self.product_unit.key(),
+            serial_number: self.product_unit.serial_number,
+            previous_owner,
+            new_owner,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct UnitRegistered {
+    pub batch_id: [u8; 32],
+    pub serial_number: [u8; 32],
+    pub unit: Pubkey,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnitTransferred {
+    pub unit: Pubkey,
+    pub serial_number: [u8; 32],
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
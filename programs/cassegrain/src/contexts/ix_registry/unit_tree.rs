@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::consts::*;
+use crate::merkle;
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct AppendUnitSerials<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR + UnitTree::INIT_SPACE,
+        seeds = [UNIT_TREE, product_batch.key().as_ref()],
+        bump,
+    )]
+    pub unit_tree: Account<'info, UnitTree>,
+
+    #[account(
+        seeds = [CONFIG],
+        bump,
+        constraint = !cassegrain_config.is_paused 
+            @ CassegrainError::ProgramPaused,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    #[account(
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32])]
+pub struct VerifyUnitMembership<'info> {
+    #[account(
+        seeds = [BATCH, product_batch.manufacturer.as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+}
+
+impl<'info> AppendUnitSerials<'info> {
+    pub fn append(
+        &mut self,
+        batch_id: [u8; 32],
+        serial_numbers: Vec<[u8; 32]>,
+        bumps: AppendUnitSerialsBumps,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        self.manufacturer.require_active(clock.unix_timestamp)?;
+        require!(!serial_numbers.is_empty(), CassegrainError::InvalidUnit);
+
+        if self.unit_tree.batch == Pubkey::default() {
+            self.unit_tree.batch = self.product_batch.key();
+            self.unit_tree.bump = bumps.unit_tree;
+        }
+
+        let first_index = self.product_batch.unit_leaf_count;
+        self.product_batch
+            .append_unit_serials(&mut self.unit_tree, &serial_numbers)?;
+        self.product_batch.last_updated = clock.unix_timestamp;
+
+        emit!(UnitSerialsAppended {
+            batch_id,
+            first_index,
+            serial_numbers,
+            merkle_root: self.product_batch.unit_merkle_root,
+            leaf_count: self.product_batch.unit_leaf_count,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> VerifyUnitMembership<'info> {
+    pub fn verify(
+        &self,
+        batch_id: [u8; 32],
+        serial_number: [u8; 32],
+        leaf_index: u32,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(
+            leaf_index < self.product_batch.unit_leaf_count
+                && merkle::verify_proof(
+                    &self.product_batch.unit_merkle_root,
+                    merkle::hash_leaf(&serial_number),
+                    leaf_index,
+                    &proof,
+                ),
+            CassegrainError::InvalidMerkleProof
+        );
+
+        emit!(UnitMembershipVerified {
+            batch_id,
+            serial_number,
+            leaf_index,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct UnitSerialsAppended {
+    pub batch_id: [u8; 32],
+    pub first_index: u32,
+    pub serial_numbers: Vec<[u8; 32]>,
+    pub merkle_root: [u8; 32],
+    pub leaf_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnitMembershipVerified {
+    pub batch_id: [u8; 32],
+    pub serial_number: [u8; 32],
+    pub leaf_index: u32,
+    pub timestamp: i64,
+}
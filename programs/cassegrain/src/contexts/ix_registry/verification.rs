@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::consts::*;
+
+#[derive(Accounts)]
+pub struct VerifyManufacturer<'info> {
+    pub verifier: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG],
+        bump,
+        constraint = cassegrain_config.is_verifier(&verifier.key()) 
+            @ CassegrainError::Unauthorized,
+        constraint = !cassegrain_config.is_paused 
+            @ CassegrainError::ProgramPaused,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    #[account(
+        mut,
+        seeds = [MANUFACTURER, manufacturer.owner.as_ref()],
+        bump,
+        // Verifiers cannot vouch for their own profile
+        constraint = manufacturer.owner != verifier.key() 
+            @ CassegrainError::Unauthorized,
+        constraint = !manufacturer.is_verified 
+            @ CassegrainError::ManufacturerAlreadyVerified,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeVerification<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [CONFIG],
+        bump,
+        constraint = cassegrain_config.is_verifier(&signer.key()) 
+            || cassegrain_config.authority == signer.key() 
+            @ CassegrainError::Unauthorized,
+        // Under a council the authority revokes through a proposal
+        constraint = cassegrain_config.is_verifier(&signer.key()) 
+            || cassegrain_config.council.is_none() 
+            @ CassegrainError::GovernedByCouncil,
+    )]
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
+
+    #[account(
+        mut,
+        seeds = [MANUFACTURER, manufacturer.owner.as_ref()],
+        bump,
+        constraint = manufacturer.is_verified 
+            @ CassegrainError::ManufacturerNotVerified,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+}
+
+impl<'info> VerifyManufacturer<'info> {
+    pub fn verify(&mut self, evidence_cid: String) -> Result<()> {
+        require!(
+            !evidence_cid.is_empty() && evidence_cid.len() <= 32,
+            CassegrainError::InvalidIPFSHash
+        );
+
+        let clock = Clock::get()?;
+
+        self.manufacturer.is_verified = true;
+        self.manufacturer.verified_by = Some(self.verifier.key());
+        self.manufacturer.verified_at = Some(clock.unix_timestamp);
+        self.manufacturer.verification_cid = Some(evidence_cid.clone());
+
+        emit!(ManufacturerVerified {
+            manufacturer: self.manufacturer.owner,
+            verified_by: self.verifier.key(),
+            evidence_cid,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> RevokeVerification<'info> {
+    pub fn revoke(&mut self) -> Result<()> {
+        let previous_verifier = self.manufacturer.verified_by;
+        self.manufacturer.clear_verification();
+
+        emit!(VerificationRevoked {
+            manufacturer: self.manufacturer.owner,
+            revoked_by: self.signer.key(),
+            previous_verifier,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct ManufacturerVerified {
+    pub manufacturer: Pubkey,
+    pub verified_by: Pubkey,
+    pub evidence_cid: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VerificationRevoked {
+    pub manufacturer: Pubkey,
+    pub revoked_by: Pubkey,
+    pub previous_verifier: Option<Pubkey>,
+    pub timestamp: i64,
+}
@@ -1,9 +1,13 @@
+pub mod ix_admin;
 pub mod ix_events;
+pub mod ix_governance;
 pub mod ix_registry;
 pub mod initialize;
 pub mod rollup;
 
+pub use ix_admin::*;
 pub use ix_events::*;
+pub use ix_governance::*;
 pub use ix_registry::*;
 pub use initialize::*;
 pub use rollup::*;
\ No newline at end of file
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::consts::*;
+use crate::state::*;
+use crate::error::*;
 
 // Magic Block SDK imports
 use ephemeral_rollups_sdk::anchor::delegate;
@@ -7,55 +9,73 @@ use ephemeral_rollups_sdk::cpi::DelegateConfig;
 
 #[delegate]
 #[derive(Accounts)]
-#[instruction(batch_id: [u8; 32], event_id: [u8; 32])]
+#[instruction(batch_id: [u8; 32], page_index: u32)]
 pub struct DelegateProduct<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
 
-    /// CHECK: The Product Batch account we are delegating to ER
+    /// CHECK: The Product Batch account we are delegating to ER, the seeds tie it to the signer
     #[account(
         mut,
         del,
-        seeds = [BATCH, batch_id.as_ref()],
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
         bump,
     )]
     pub product_batch: AccountInfo<'info>,
 
-    /// CHECK: The Product Event account we are delegating to ER
+    /// CHECK: The event page rollup updates are appended to while delegated
     #[account(
         mut,
         del,
-        seeds = [EVENT, event_id.as_ref()],
+        seeds = [EVENT_PAGE, product_batch.key().as_ref(), page_index.to_le_bytes().as_ref()],
         bump,
     )]
-    pub product_event: AccountInfo<'info>,
+    pub event_page: AccountInfo<'info>,
 
+    /// CHECK: Only checked for emptiness, a batch being archived can not go back to the rollup
+    #[account(
+        seeds = [ARCHIVE, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+        constraint = batch_archive.data_is_empty() 
+            @ CassegrainError::BatchBeingArchived,
+    )]
+    pub batch_archive: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
 }
 
 impl<'info> DelegateProduct<'info> {
     pub fn delegate_to_rollup(
         &mut self,
         batch_id: [u8; 32],
-        event_id: [u8; 32],
+        page_index: u32,
     ) -> Result<()> {
+        self.manufacturer.require_active(Clock::get()?.unix_timestamp)?;
+
         msg!("Delegating supply chain accounts to Magic Block Ephemeral Rollup...");
         
         // Delegate Product Batch account
         self.delegate_product_batch(
             &self.signer,
-            &[BATCH, batch_id.as_ref()],
+            &[BATCH, self.signer.key().as_ref(), batch_id.as_ref()],
             DelegateConfig::default(),
         )?;
         
-        // Delegate Product Event account  
-        self.delegate_product_event(
+        // Delegate the Event Page account
+        self.delegate_event_page(
             &self.signer,
-            &[EVENT, event_id.as_ref()],
+            &[EVENT_PAGE, self.product_batch.key().as_ref(), page_index.to_le_bytes().as_ref()],
             DelegateConfig::default(),
         )?;
         
         msg!(
-            "Successfully delegated batch and event to ephemeral rollup",
+            "Successfully delegated batch and event page to ephemeral rollup",
         );
         Ok(())
     }
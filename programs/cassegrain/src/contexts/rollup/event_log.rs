@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 use crate::consts::*;
 use crate::state::*;
+use crate::error::*;
 
 // Magic Block SDK imports for commit
 use ephemeral_rollups_sdk::anchor::commit;
@@ -8,7 +9,7 @@ use ephemeral_rollups_sdk::ephem::commit_accounts;
 
 #[commit]
 #[derive(Accounts)]
-#[instruction(batch_id: [u8; 32], event_id: [u8; 32])]
+#[instruction(batch_id: [u8; 32], page_index: u32)]
 pub struct RollupEventLog<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
@@ -16,86 +17,138 @@ pub struct RollupEventLog<'info> {
     /// The delegated Product Batch account (already on rollup)
     #[account(
         mut,
-        seeds = [BATCH, batch_id.as_ref()],
+        seeds = [BATCH, product_batch.manufacturer.as_ref(), batch_id.as_ref()],
         bump,
     )]
     pub product_batch: Account<'info, ProductBatch>,
 
-    /// The delegated Product Event account (already on rollup)
+    /// The delegated Event Page account, must be the batch's current page
     #[account(
         mut,
-        seeds = [EVENT, event_id.as_ref()],
+        seeds = [EVENT_PAGE, product_batch.key().as_ref(), page_index.to_le_bytes().as_ref()],
+        bump = event_page.bump,
+        constraint = event_page.page_index as usize == product_batch.rollup_records as usize / EVENT_PAGE_SIZE 
+            @ CassegrainError::InvalidEventPage,
+    )]
+    pub event_page: Account<'info, EventPage>,
+
+    /// Profile of the signer, decides which event types it may log
+    #[account(
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key() 
+            @ CassegrainError::Unauthorized,
+        constraint = manufacturer.is_verified 
+            @ CassegrainError::ManufacturerNotVerified,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+
+    /// Profile the batch is registered under, a suspended owner freezes its batches
+    #[account(
+        seeds = [MANUFACTURER, product_batch.manufacturer.as_ref()],
+        bump,
+    )]
+    pub batch_owner: Account<'info, ManufacturerProfile>,
+
+    /// Required when the signer is not the batch manufacturer
+    #[account(
+        seeds = [HANDLER, product_batch.key().as_ref(), signer.key().as_ref()],
+        bump = batch_handler.bump,
+    )]
+    pub batch_handler: Option<Account<'info, BatchHandler>>,
+
+    /// Read only on the rollup, cloned from the base layer
+    #[account(
+        seeds = [CONFIG],
         bump,
+        constraint = !cassegrain_config.is_paused 
+            @ CassegrainError::ProgramPaused,
     )]
-    pub product_event: Account<'info, ProductEvent>,
+    pub cassegrain_config: Account<'info, CassegrainConfig>,
 }
 
 impl<'info> RollupEventLog<'info> {
+    #[allow(clippy::too_many_arguments)]
     pub fn update_supply_chain_state(
         &mut self,
-        _batch_id: [u8; 32],
-        _event_id: [u8; 32],
-        // Fields to update
+        batch_id: [u8; 32],
+        _page_index: u32,
+        // Contents of the appended record
         new_product_status: Option<ProductStatus>,
         new_order_status: Option<OrderStatus>, 
-        new_event_type: Option<EventType>,
-        previous_event: Option<Pubkey>,
-        next_event: Option<Pubkey>,
+        event_type: EventType,
         metadata_ipfs: Option<String>,
+        quantity: Option<u64>,
+        location: Option<Location>,
     ) -> Result<()> {
         let clock = Clock::get()?;
         
-        msg!("🔄 Updating supply chain state on rollup...");
-
-      
+        msg!("🔄 Appending supply chain update on rollup...");
+
+        self.manufacturer.require_active(clock.unix_timestamp)?;
+        self.batch_owner.require_active(clock.unix_timestamp)?;
+        self.product_batch
+            .authorize_actor(&self.signer.key(), self.batch_handler.as_deref())?;
+        event_type.authorize(self.manufacturer.business_type)?;
+        event_type.check_location(location.as_ref())?;
+        if let Some(ref ipfs) = metadata_ipfs {
+            require!(ipfs.len() <= 32, CassegrainError::InvalidIPFSHash);
+        }
+        self.product_batch.apply_event_quantity(event_type, quantity)?;
 
-        // 1. Update ProductBatch status if provided
+        // 1. Move the batch along, both transitions are checked
         if let Some(status) = new_product_status {
-            self.product_batch.status = status;
+            self.product_batch.transition_status(status)?;
             msg!("📦 Batch status updated to: {:?}", status);
         }
 
-        // 2. Update ProductEvent fields
-        if let Some(event_type) = new_event_type {
-            self.product_event.product_event_type = event_type;
-            msg!("📝 Event type updated to: {:?}", event_type);
-        }
-
         if let Some(order_status) = new_order_status {
-            self.product_event.order_status = order_status;
+            self.product_batch.transition_order_status(order_status)?;
             msg!("📋 Order status updated to: {:?}", order_status);
         }
 
-        if let Some(prev_event) = previous_event {
-            self.product_event.previous_event = Some(prev_event);
-            msg!("🔗 Previous event linked: {:?}", prev_event);
-        }
-
-        if let Some(next_event) = next_event {
-            self.product_event.next_event = Some(next_event);
-            msg!("🔗 Next event linked: {:?}", next_event);
-        }
-
-        if let Some(ipfs) = metadata_ipfs {
-            self.product_event.metadata_ipfs = Some(ipfs.clone());
-            msg!("📎 Metadata IPFS updated: {}", ipfs);
-        }
-
-        // 3. Update timestamps
-        self.product_event.timestamp = clock.unix_timestamp;
-        self.product_batch.last_updated = clock.unix_timestamp;
-
-        // 4. Increment event counter
-        self.product_batch.total_events += 1;
+        // 2. Append an immutable record, earlier records are never touched
+        let (product_status, order_status) =
+            (self.product_batch.status, self.product_batch.order_status);
+        let record = self.product_batch.append_record(
+            &self.cassegrain_config,
+            EventRecord {
+                sequence: 0,
+                history_index: 0,
+                event_type,
+                product_status,
+                order_status,
+                actor: self.signer.key(),
+                timestamp: clock.unix_timestamp,
+                metadata_ipfs,
+                quantity,
+                location,
+                prev_hash: [0u8; 32],
+                record_hash: [0u8; 32],
+            },
+        )?;
+        self.event_page.records.push(record.clone());
 
         // Log current state
         msg!("📊 Updated State Summary:");
         msg!("   Batch Status: {:?}", self.product_batch.status);
-        msg!("   Event Type: {:?}", self.product_event.product_event_type);
-        msg!("   Order Status: {:?}", self.product_event.order_status);
-        msg!("   Total Events: {}", self.product_batch.total_events);
+        msg!("   Event Type: {:?}", record.event_type);
+        msg!("   Order Status: {:?}", record.order_status);
+        msg!("   Rollup Records: {}", self.product_batch.rollup_records);
         msg!("   Timestamp: {}", clock.unix_timestamp);
 
+        emit!(StateUpdated {
+            batch_id,
+            sequence: record.sequence,
+            updated_by: record.actor,
+            batch_status: record.product_status,
+            order_status: record.order_status,
+            event_type,
+            location: record.location.clone(),
+            record_hash: record.record_hash,
+            timestamp: clock.unix_timestamp,
+        });
+
         // Commit all changes back to mainnet
         msg!("💾 Committing state updates to mainnet...");
         
@@ -103,7 +156,7 @@ impl<'info> RollupEventLog<'info> {
             &self.signer,
             vec![
                 &self.product_batch.to_account_info(),
-                &self.product_event.to_account_info(),
+                &self.event_page.to_account_info(),
             ],
             &self.magic_context,
             &self.magic_program,
@@ -119,10 +172,12 @@ impl<'info> RollupEventLog<'info> {
 #[event]
 pub struct StateUpdated {
     pub batch_id: [u8; 32],
-    pub event_id: [u8; 32],
+    pub sequence: u32,
     pub updated_by: Pubkey,
     pub batch_status: ProductStatus,
     pub order_status: OrderStatus,
     pub event_type: EventType,
+    pub location: Option<Location>,
+    pub record_hash: [u8; 32],
     pub timestamp: i64,
-}
\ No newline at end of file
+}
@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+use crate::consts::*;
+use crate::state::*;
+use crate::error::*;
+
+// Magic Block SDK imports
+use ephemeral_rollups_sdk::anchor::{commit, delegate};
+use ephemeral_rollups_sdk::cpi::DelegateConfig;
+use ephemeral_rollups_sdk::ephem::commit_and_undelegate_accounts;
+
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32], page_index: u32)]
+pub struct OpenEventPage<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: Only its address is used, so a page can be opened while the batch is delegated
+    #[account(
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = signer,
+        space = ANCHOR_DISCRIMINATOR + EventPage::INIT_SPACE,
+        seeds = [EVENT_PAGE, product_batch.key().as_ref(), page_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub event_page: Account<'info, EventPage>,
+
+    /// CHECK: Only checked to exist, it may be delegated. Pages are opened in order so
+    /// `finalize_archive` can tell the batch has no pages past the ones it closed.
+    #[account(
+        seeds = [EVENT_PAGE, product_batch.key().as_ref(), page_index.saturating_sub(1).to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub previous_event_page: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenEventPage<'info> {
+    pub fn open(&mut self, page_index: u32, bumps: OpenEventPageBumps) -> Result<()> {
+        self.manufacturer.require_active(Clock::get()?.unix_timestamp)?;
+        require!(
+            page_index == 0
+                || self.previous_event_page.as_ref().is_some_and(|page| !page.data_is_empty()),
+            CassegrainError::InvalidEventPage
+        );
+
+        self.event_page.set_inner(EventPage {
+            batch: self.product_batch.key(),
+            page_index,
+            records: Vec::new(),
+            bump: bumps.event_page,
+        });
+        Ok(())
+    }
+}
+
+/// Hands a further page to the rollup while the batch itself stays delegated
+#[delegate]
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32], page_index: u32)]
+pub struct DelegateEventPage<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// CHECK: Only its address is used, the batch is usually delegated already
+    #[account(
+        seeds = [BATCH, signer.key().as_ref(), batch_id.as_ref()],
+        bump,
+    )]
+    pub product_batch: UncheckedAccount<'info>,
+
+    /// CHECK: An opened event page of the batch, delegated to ER
+    #[account(
+        mut,
+        del,
+        seeds = [EVENT_PAGE, product_batch.key().as_ref(), page_index.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub event_page: AccountInfo<'info>,
+
+    #[account(
+        seeds = [MANUFACTURER, signer.key().as_ref()],
+        bump,
+        constraint = manufacturer.owner == signer.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub manufacturer: Account<'info, ManufacturerProfile>,
+}
+
+/// Returns a page to the base layer without undelegating the batch, for filled pages
+#[commit]
+#[derive(Accounts)]
+#[instruction(batch_id: [u8; 32], page_index: u32)]
+pub struct UndelegateEventPage<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [BATCH, product_batch.manufacturer.as_ref(), batch_id.as_ref()],
+        bump,
+        constraint = product_batch.manufacturer == signer.key() 
+            @ CassegrainError::Unauthorized,
+    )]
+    pub product_batch: Account<'info, ProductBatch>,
+
+    #[account(
+        mut,
+        seeds = [EVENT_PAGE, product_batch.key().as_ref(), page_index.to_le_bytes().as_ref()],
+        bump = event_page.bump,
+    )]
+    pub event_page: Account<'info, EventPage>,
+}
+
+impl<'info> DelegateEventPage<'info> {
+    pub fn delegate_page(&mut self, page_index: u32) -> Result<()> {
+        self.manufacturer.require_active(Clock::get()?.unix_timestamp)?;
+
+        self.delegate_event_page(
+            &self.signer,
+            &[EVENT_PAGE, self.product_batch.key().as_ref(), page_index.to_le_bytes().as_ref()],
+            DelegateConfig::default(),
+        )?;
+
+        msg!("Delegated event page {} to ephemeral rollup", page_index);
+        Ok(())
+    }
+}
+
+impl<'info> UndelegateEventPage<'info> {
+    pub fn undelegate_page(&mut self, page_index: u32) -> Result<()> {
+        commit_and_undelegate_accounts(
+            &self.signer,
+            vec![&self.event_page.to_account_info()],
+            &self.magic_context,
+            &self.magic_program,
+        )?;
+
+        msg!("Committed and undelegated event page {}", page_index);
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
 pub mod delegate;
 pub use delegate::*;
 
+pub mod event_page;
+pub use event_page::*;
+
 
 pub mod event_log;
 pub use event_log::*;
@@ -9,7 +9,7 @@ use ephemeral_rollups_sdk::ephem::commit_and_undelegate_accounts;
 
 #[commit]
 #[derive(Accounts)]
-#[instruction(batch_id: [u8; 32], event_id: [u8; 32])]
+#[instruction(batch_id: [u8; 32], page_index: u32)]
 pub struct UndelegateProduct<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
@@ -17,31 +17,27 @@ pub struct UndelegateProduct<'info> {
     /// The delegated Product Batch account (to be undelegated)
     #[account(
         mut,
-        seeds = [BATCH, batch_id.as_ref()],
+        seeds = [BATCH, product_batch.manufacturer.as_ref(), batch_id.as_ref()],
         bump,
         constraint = product_batch.manufacturer == signer.key() 
             @ CassegrainError::Unauthorized,
     )]
     pub product_batch: Account<'info, ProductBatch>,
 
-    /// The delegated Product Event account (to be undelegated)
+    /// The delegated Event Page account (to be undelegated)
     #[account(
         mut,
-        seeds = [EVENT, event_id.as_ref()],
-        bump,
-        constraint = product_event.batch_id == batch_id 
-            @ CassegrainError::InvalidBatchId,
-        constraint = product_event.event_id == event_id 
-            @ CassegrainError::InvalidEventId,
+        seeds = [EVENT_PAGE, product_batch.key().as_ref(), page_index.to_le_bytes().as_ref()],
+        bump = event_page.bump,
     )]
-    pub product_event: Account<'info, ProductEvent>,
+    pub event_page: Account<'info, EventPage>,
 }
 
 impl<'info> UndelegateProduct<'info> {
     pub fn undelegate(
         &mut self,
         batch_id: [u8; 32],
-        event_id: [u8; 32],
+        page_index: u32,
     ) -> Result<()> {
       
         msg!("💾 Committing final state and undelegating from rollup...");
@@ -52,7 +48,7 @@ impl<'info> UndelegateProduct<'info> {
             &self.signer,
             vec![
                 &self.product_batch.to_account_info(),
-                &self.product_event.to_account_info(),
+                &self.event_page.to_account_info(),
             ],
             &self.magic_context,
             &self.magic_program,
@@ -65,11 +61,12 @@ impl<'info> UndelegateProduct<'info> {
         // Emit completion event
         emit!(SupplyChainCompleted {
             batch_id,
-            event_id,
+            page_index,
             final_status: self.product_batch.status,
-            final_order_status: self.product_event.order_status,
-            verification_status: self.product_event.verification_status,
+            final_order_status: self.product_batch.order_status,
             total_events: self.product_batch.total_events,
+            rollup_records: self.product_batch.rollup_records,
+            history_digest: self.product_batch.history_digest,
             completed_by: self.signer.key(),
             completion_timestamp: clock.unix_timestamp,
         });
@@ -84,11 +81,12 @@ impl<'info> UndelegateProduct<'info> {
 #[event]
 pub struct SupplyChainCompleted {
     pub batch_id: [u8; 32],
-    pub event_id: [u8; 32],
+    pub page_index: u32,
     pub final_status: ProductStatus,
     pub final_order_status: OrderStatus,
-    pub verification_status: VerificationStatus,
     pub total_events: u32,
+    pub rollup_records: u32,
+    pub history_digest: [u8; 32],
     pub completed_by: Pubkey,
     pub completion_timestamp: i64,
 }
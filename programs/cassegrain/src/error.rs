@@ -68,4 +68,145 @@ pub enum CassegrainError {
     
     #[msg("Invalid event ID")]
     InvalidEventId,
-}
\ No newline at end of file
+
+    #[msg("Invalid configuration value")]
+    InvalidConfigValue,
+
+    #[msg("Program is not paused")]
+    ProgramNotPaused,
+
+    #[msg("Invalid authority")]
+    InvalidAuthority,
+
+    #[msg("No authority transfer is pending")]
+    NoPendingAuthority,
+
+    #[msg("Account does not match the legacy layout")]
+    InvalidLegacyAccount,
+
+    #[msg("Fee treasury does not match the config")]
+    InvalidFeeTreasury,
+
+    #[msg("Treasury balance too low for this withdrawal")]
+    InsufficientTreasuryBalance,
+
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+
+    #[msg("Manufacturer reached the maximum number of active batches")]
+    ManufacturerBatchLimitReached,
+
+    #[msg("Batch already has events")]
+    BatchHasEvents,
+
+    #[msg("Verifier already appointed")]
+    VerifierAlreadyExists,
+
+    #[msg("Verifier not found")]
+    VerifierNotFound,
+
+    #[msg("Maximum number of verifiers reached")]
+    TooManyVerifiers,
+
+    #[msg("Manufacturer already verified")]
+    ManufacturerAlreadyVerified,
+
+    #[msg("Business type not allowed to log this event type")]
+    UnauthorizedEventType,
+
+    #[msg("This action is governed by the admin council")]
+    GovernedByCouncil,
+
+    #[msg("Invalid council members or threshold")]
+    InvalidCouncil,
+
+    #[msg("Signer is not a council member")]
+    NotCouncilMember,
+
+    #[msg("Proposal already approved by this member")]
+    AlreadyApproved,
+
+    #[msg("Proposal already executed")]
+    ProposalAlreadyExecuted,
+
+    #[msg("Proposal has not reached the approval threshold")]
+    ThresholdNotReached,
+
+    #[msg("Manufacturer is suspended")]
+    ManufacturerSuspended,
+
+    #[msg("Manufacturer is not suspended")]
+    ManufacturerNotSuspended,
+
+    #[msg("Profile fields are empty or too long")]
+    InvalidProfileData,
+
+    #[msg("Manufacturer still has live batches")]
+    ManufacturerHasLiveBatches,
+
+    #[msg("Units would exceed the batch size")]
+    UnitLimitExceeded,
+
+    #[msg("Unit does not belong to this batch")]
+    InvalidUnit,
+
+    #[msg("Unit already exists")]
+    UnitAlreadyExists,
+
+    #[msg("Unit serial is not part of this batch")]
+    InvalidMerkleProof,
+
+    #[msg("Quantities do not add up to the source batches")]
+    QuantityMismatch,
+
+    #[msg("Batches cannot be combined")]
+    IncompatibleBatches,
+
+    #[msg("Component batch has been recalled")]
+    ComponentRecalled,
+
+    #[msg("Invalid bill of materials")]
+    InvalidBillOfMaterials,
+
+    #[msg("Quantity exceeds what remains in the batch")]
+    InsufficientQuantity,
+
+    #[msg("Metadata history is full")]
+    MetadataHistoryFull,
+
+    #[msg("Batch is not in a terminal state or still within its retention window")]
+    BatchNotArchivable,
+
+    #[msg("Event does not belong to this batch's event chain")]
+    InvalidEventChain,
+
+    #[msg("Order status can not move to the requested status")]
+    InvalidOrderStatusTransition,
+
+    #[msg("Event page is not the batch's current page")]
+    InvalidEventPage,
+
+    #[msg("Too many accounts for one archive step")]
+    TooManyArchiveAccounts,
+
+    #[msg("Batch is being archived")]
+    BatchBeingArchived,
+
+    #[msg("Accounts passed with the proposal do not match its action")]
+    InvalidProposalAccounts,
+
+    #[msg("Batch is part of a split or merge lineage")]
+    BatchHasLineage,
+
+    #[msg("Batch has registered units")]
+    BatchHasUnits,
+
+    #[msg("Suspension expiry must be in the future")]
+    InvalidSuspensionExpiry,
+
+    #[msg("Account can not be closed along with this batch")]
+    InvalidArchiveAccount,
+
+    #[msg("Units, grants, bill of materials or event pages of the batch are still open")]
+    BatchAccountsOpen,
+}
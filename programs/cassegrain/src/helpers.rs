@@ -0,0 +1,176 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+/// Closes an account this program owns without deserializing it,
+/// for accounts whose layout no longer matches the current type.
+pub fn close_raw_account<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let lamports = account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(crate::error::CassegrainError::MathOverflow)?;
+    account.assign(&system_program::ID);
+    account.resize(0)?;
+    Ok(())
+}
+
+/// Creates and writes a program owned PDA by hand, for instructions that
+/// create a variable number of accounts passed through `remaining_accounts`.
+pub fn init_pda_account<'info, T: AccountSerialize>(
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+    space: usize,
+    value: &T,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let signer: &[&[&[u8]]] = &[signer_seeds];
+
+    if account.lamports() == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+                signer,
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        )?;
+    } else {
+        // Someone may have pre-funded the address, same handling as Anchor's `init`
+        require!(
+            account.owner == &system_program::ID && account.data_is_empty(),
+            ErrorCode::AccountNotSystemOwned
+        );
+        let top_up = rent.saturating_sub(account.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    system_program::Transfer {
+                        from: payer.clone(),
+                        to: account.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Allocate {
+                    account_to_allocate: account.clone(),
+                },
+                signer,
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Assign {
+                    account_to_assign: account.clone(),
+                },
+                signer,
+            ),
+            &crate::ID,
+        )?;
+    }
+
+    let mut data = account.try_borrow_mut_data()?;
+    value.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+/// Fails when `batch_id` was archived under `manufacturer`, archived ids are never
+/// handed out again. `batch_archive` must be the `[ARCHIVE, manufacturer, batch_id]` PDA.
+pub fn require_batch_id_unused(
+    batch_archive: &AccountInfo,
+    manufacturer: &Pubkey,
+    batch_id: &[u8; 32],
+) -> Result<()> {
+    let (expected, _) = Pubkey::find_program_address(
+        &[crate::consts::ARCHIVE, manufacturer.as_ref(), batch_id.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(
+        batch_archive.key(),
+        expected,
+        crate::error::CassegrainError::InvalidBatchId
+    );
+    require!(
+        batch_archive.data_is_empty(),
+        crate::error::CassegrainError::ProductAlreadyExists
+    );
+    Ok(())
+}
+
+/// Moves `amount` out of the `[TREASURY]` vault, keeping it rent exempt so it
+/// can still receive fees. Shared by `withdraw_fees` and council proposals.
+pub fn withdraw_from_treasury<'info>(
+    treasury: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    treasury_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let available = treasury
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(0));
+    require!(
+        amount > 0 && amount <= available,
+        crate::error::CassegrainError::InsufficientTreasuryBalance
+    );
+
+    let signer_seeds: &[&[&[u8]]] = &[&[crate::consts::TREASURY, &[treasury_bump]]];
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Transfer {
+                from: treasury.clone(),
+                to: destination.clone(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )
+}
+
+/// Charges the config's `product_registration_fee` for one new batch,
+/// every instruction that creates a batch pays it.
+pub fn charge_registration_fee<'info>(
+    payer: &AccountInfo<'info>,
+    fee_treasury: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    config: &crate::state::CassegrainConfig,
+) -> Result<()> {
+    let fee = config.product_registration_fee;
+    if fee == 0 {
+        return Ok(());
+    }
+
+    require!(
+        payer.lamports() >= fee,
+        crate::error::CassegrainError::InsufficientRegistrationFee
+    );
+
+    system_program::transfer(
+        CpiContext::new(
+            system_program.clone(),
+            system_program::Transfer {
+                from: payer.clone(),
+                to: fee_treasury.clone(),
+            },
+        ),
+        fee,
+    )
+}
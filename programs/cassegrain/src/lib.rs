@@ -1,3 +1,5 @@
+#![allow(unexpected_cfgs)]
+
 use anchor_lang::prelude::*;
 pub mod state;
 pub use state::*;
@@ -6,6 +8,8 @@ pub mod contexts;
 pub use contexts::*;
 pub mod consts;
 pub mod error;
+pub mod helpers;
+pub mod merkle;
 // pub use contexts::*;
 
 use ephemeral_rollups_sdk::anchor::ephemeral;
@@ -13,96 +17,432 @@ use ephemeral_rollups_sdk::anchor::ephemeral;
 
 declare_id!("5oNsyMZ8XYiGibvzHtm8Y6QzBSpm3i5TrdnpLaevHTU9");
 
-#[ephemeral]
-#[program]
-pub mod cassegrain {
+// `#[program]` emits its IDL handlers next to the program module rather than
+// inside it, and they still call the deprecated `AccountInfo::realloc`. Wrapping
+// the program keeps that allow off the rest of the crate.
+#[allow(deprecated)]
+mod program_entry {
     use super::*;
 
-    pub fn initialize(
-        ctx: Context<Initialize>,
-        product_registration_fee: u64, 
-        max_events_per_product: u32, 
-        max_products_per_manufacturer: u32, 
-        min_event_interval: i64, 
-        max_batch_size: u8,
-    ) -> Result<()> {
-        ctx.accounts.initialize(product_registration_fee, max_events_per_product, max_products_per_manufacturer, min_event_interval, max_batch_size, ctx.bumps)
-    }
+    #[ephemeral]
+    #[program]
+    pub mod cassegrain {
+        use super::*;
 
-    pub fn register_manufacturer (
-        ctx: Context<RegisterProfile>,
-        company_name: String,
-        business_type: BusinessType,
-        certifications: String,
-    ) -> Result<()> {
-        ctx.accounts.register(company_name, business_type, certifications, ctx.bumps)
+        pub fn initialize(
+            ctx: Context<Initialize>,
+            product_registration_fee: u64, 
+            max_events_per_product: u32, 
+            max_products_per_manufacturer: u32, 
+            min_event_interval: i64, 
+            max_batch_size: u64,
+        ) -> Result<()> {
+            ctx.accounts.initialize(product_registration_fee, max_events_per_product, max_products_per_manufacturer, min_event_interval, max_batch_size, ctx.bumps)
+        }
 
-    }
+        pub fn register_manufacturer (
+            ctx: Context<RegisterProfile>,
+            company_name: String,
+            business_type: BusinessType,
+            certifications: String,
+        ) -> Result<()> {
+            ctx.accounts.register(company_name, business_type, certifications, ctx.bumps)
 
-    /// Register a new product
-    pub fn register_product_batch(
-        ctx: Context<RegisterProduct>,
-        batch_id: [u8; 32],
-        metadata_ipfs: Option<String>, 
-        category: ProductCategory,
-        batch_size: u8,
-    ) -> Result<()> {
-        ctx.accounts.register(
-            batch_id, 
-            metadata_ipfs, 
-            category, 
-            batch_size, 
-            ctx.bumps
-        )
-    }
+        }
 
-    //create event 
-
-    pub fn create_event(
-        ctx: Context<CreateEvent>,
-        batch_id: [u8; 32],
-        event_id: [u8; 32],
-        event_type: EventType,
-        metadata_ipfs: Option<String>,
-        order_status: OrderStatus,
-        previous_event: Option<Pubkey>,
-    ) -> Result<()> {
-       ctx.accounts.create_event(batch_id, event_id, event_type, metadata_ipfs, order_status, previous_event, ctx.bumps)
-    }
+        /// Update the caller's profile, identity changes reset verification
+        pub fn update_profile(
+            ctx: Context<UpdateProfile>,
+            company_name: Option<String>,
+            business_type: Option<BusinessType>,
+            certifications: Option<String>,
+        ) -> Result<()> {
+            ctx.accounts.update(company_name, business_type, certifications)
+        }
 
-    /// delegate event 
-    /// 
+        /// Close the caller's profile once it has no live batches
+        pub fn close_profile(ctx: Context<CloseProfile>) -> Result<()> {
+            ctx.accounts.close()
+        }
 
-    pub fn delegate_product(
-        ctx: Context<DelegateProduct>,
-        batch_id: [u8; 32],
-        event_id: [u8; 32],
-    ) -> Result<()> {
-       ctx.accounts.delegate_to_rollup(batch_id, event_id)
-    }
+        /// Update the tunable config values, authority only
+        pub fn update_config(
+            ctx: Context<UpdateConfig>,
+            update: ConfigUpdate,
+        ) -> Result<()> {
+            ctx.accounts.update_config(update)
+        }
+
+        pub fn pause(ctx: Context<UpdateConfig>) -> Result<()> {
+            ctx.accounts.set_paused(true)
+        }
+
+        pub fn unpause(ctx: Context<UpdateConfig>) -> Result<()> {
+            ctx.accounts.set_paused(false)
+        }
+
+        /// Propose a new config authority, takes effect once accepted
+        pub fn propose_authority(
+            ctx: Context<ProposeAuthority>,
+            new_authority: Pubkey,
+        ) -> Result<()> {
+            ctx.accounts.propose_authority(new_authority)
+        }
+
+        pub fn cancel_authority_transfer(ctx: Context<ProposeAuthority>) -> Result<()> {
+            ctx.accounts.cancel_authority_transfer()
+        }
+
+        pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+            ctx.accounts.accept_authority()
+        }
+
+        /// Move a config from the old `[CONFIG, authority]` address to `[CONFIG]`
+        pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+            ctx.accounts.migrate(ctx.bumps)
+        }
+
+        pub fn set_fee_treasury(
+            ctx: Context<UpdateConfig>,
+            fee_treasury: Pubkey,
+        ) -> Result<()> {
+            ctx.accounts.set_fee_treasury(fee_treasury)
+        }
+
+        /// Withdraw collected registration fees from the program treasury
+        pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+            ctx.accounts.withdraw(amount, ctx.bumps)
+        }
+
+        /// Override `max_events_per_product` for one batch, `None` restores the default
+        pub fn set_batch_event_limit(
+            ctx: Context<SetBatchEventLimit>,
+            batch_id: [u8; 32],
+            max_events: Option<u32>,
+        ) -> Result<()> {
+            ctx.accounts.set_event_limit(batch_id, max_events)
+        }
+
+        /// Appoint a key allowed to verify manufacturer profiles
+        pub fn add_verifier(ctx: Context<ManageVerifiers>, verifier: Pubkey) -> Result<()> {
+            ctx.accounts.add_verifier(verifier)
+        }
+
+        pub fn remove_verifier(ctx: Context<ManageVerifiers>, verifier: Pubkey) -> Result<()> {
+            ctx.accounts.remove_verifier(verifier)
+        }
+
+        pub fn verify_manufacturer(
+            ctx: Context<VerifyManufacturer>,
+            evidence_cid: String,
+        ) -> Result<()> {
+            ctx.accounts.verify(evidence_cid)
+        }
+
+        /// Verifiers can always revoke, the authority only until a council takes over
+        pub fn revoke_verification(ctx: Context<RevokeVerification>) -> Result<()> {
+            ctx.accounts.revoke()
+        }
+
+        /// Hand config governance to an M-of-N council, authority only and one way
+        pub fn create_council(
+            ctx: Context<CreateCouncil>,
+            members: Vec<Pubkey>,
+            threshold: u8,
+        ) -> Result<()> {
+            ctx.accounts.create(members, threshold, ctx.bumps)
+        }
+
+        pub fn propose(ctx: Context<Propose>, action: CouncilAction) -> Result<()> {
+            ctx.accounts.propose(action, ctx.bumps)
+        }
+
+        pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+            ctx.accounts.approve()
+        }
+
+        /// Withdrawals, suspensions and batch event limits pass their target
+        /// accounts through `remaining_accounts`
+        pub fn execute_proposal<'info>(
+            ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>,
+        ) -> Result<()> {
+            ctx.accounts.execute(ctx.remaining_accounts)
+        }
+
+        /// Suspend one manufacturer, e.g. during a fraud investigation
+        pub fn suspend_manufacturer(
+            ctx: Context<ManageSuspension>,
+            reason_code: u16,
+            expires_at: Option<i64>,
+        ) -> Result<()> {
+            ctx.accounts.suspend(reason_code, expires_at)
+        }
+
+        pub fn lift_suspension(ctx: Context<ManageSuspension>) -> Result<()> {
+            ctx.accounts.lift()
+        }
+
+        /// Register a new product
+        pub fn register_product_batch(
+            ctx: Context<RegisterProduct>,
+            batch_id: [u8; 32],
+            metadata_ipfs: Option<String>, 
+            category: ProductCategory,
+            batch_size: u64,
+            unit_of_measure: UnitOfMeasure,
+        ) -> Result<()> {
+            ctx.accounts.register(
+                batch_id, 
+                metadata_ipfs, 
+                category, 
+                batch_size, 
+                unit_of_measure,
+                ctx.bumps
+            )
+        }
+
+        /// Close a batch that never logged an event, on chain or on the rollup, and reclaim
+        /// its rent. Batches with any history are closed through `archive_product_batch`.
+        pub fn close_product_batch(
+            ctx: Context<CloseProductBatch>,
+            batch_id: [u8; 32],
+        ) -> Result<()> {
+            ctx.accounts.close(batch_id)
+        }
+
+        /// Grow a profile registered before verification to the current layout
+        pub fn migrate_profile(ctx: Context<MigrateProfile>) -> Result<()> {
+            ctx.accounts.migrate()
+        }
+
+        /// Move a batch from the old `[BATCH, batch_id]` address under its manufacturer
+        pub fn migrate_product_batch(
+            ctx: Context<MigrateProductBatch>,
+            batch_id: [u8; 32],
+        ) -> Result<()> {
+            ctx.accounts.migrate(batch_id, ctx.bumps)
+        }
+
+        /// Register one serialized unit of a batch
+        pub fn register_product_unit(
+            ctx: Context<RegisterProductUnit>,
+            batch_id: [u8; 32],
+            serial_number: [u8; 32],
+            metadata_ipfs: Option<String>,
+            owner: Option<Pubkey>,
+        ) -> Result<()> {
+            ctx.accounts.register(batch_id, serial_number, metadata_ipfs, owner, ctx.bumps)
+        }
+
+        /// Register many units at once, unit PDAs are passed as remaining accounts in order
+        pub fn register_product_units<'info>(
+            ctx: Context<'_, '_, 'info, 'info, RegisterProductUnits<'info>>,
+            batch_id: [u8; 32],
+            units: Vec<UnitRegistration>,
+        ) -> Result<()> {
+            ctx.accounts.register(batch_id, units, ctx.remaining_accounts)
+        }
+
+        pub fn transfer_product_unit(
+            ctx: Context<TransferProductUnit>,
+            new_owner: Pubkey,
+        ) -> Result<()> {
+            ctx.accounts.transfer(new_owner)
+        }
+
+        /// Append compressed unit serials to the batch's Merkle tree
+        pub fn append_unit_serials(
+            ctx: Context<AppendUnitSerials>,
+            batch_id: [u8; 32],
+            serial_numbers: Vec<[u8; 32]>,
+        ) -> Result<()> {
+            ctx.accounts.append(batch_id, serial_numbers, ctx.bumps)
+        }
+
+        /// Check that a scanned serial belongs to the batch, fails on a bad proof
+        pub fn verify_unit_membership(
+            ctx: Context<VerifyUnitMembership>,
+            batch_id: [u8; 32],
+            serial_number: [u8; 32],
+            leaf_index: u32,
+            proof: Vec<[u8; 32]>,
+        ) -> Result<()> {
+            ctx.accounts.verify(batch_id, serial_number, leaf_index, proof)
+        }
+
+        /// Split a batch into child batches. Remaining accounts are `(child batch PDA,
+        /// child archive PDA)` pairs in the order of `children`.
+        pub fn split_batch<'info>(
+            ctx: Context<'_, '_, 'info, 'info, SplitBatch<'info>>,
+            batch_id: [u8; 32],
+            children: Vec<ChildBatch>,
+        ) -> Result<()> {
+            ctx.accounts.split(batch_id, children, ctx.remaining_accounts)
+        }
+
+        /// Merge the batches passed as remaining accounts into a new batch. Another
+        /// owner's batch is followed by the signer's grant for it and the owner's profile.
+        pub fn merge_batches<'info>(
+            ctx: Context<'_, '_, 'info, 'info, MergeBatches<'info>>,
+            batch_id: [u8; 32],
+            metadata_ipfs: Option<String>,
+        ) -> Result<()> {
+            ctx.accounts.merge(batch_id, metadata_ipfs, ctx.remaining_accounts, ctx.bumps)
+        }
+
+        /// Publish a new metadata document for a batch, keeping the previous one in its history
+        pub fn update_batch_metadata(
+            ctx: Context<UpdateBatchMetadata>,
+            batch_id: [u8; 32],
+            metadata_ipfs: String,
+        ) -> Result<()> {
+            ctx.accounts.update_metadata(batch_id, metadata_ipfs)
+        }
+
+        /// Fold part of a finished batch's history into its tombstone and close it.
+        /// Remaining accounts are up to `MAX_ARCHIVE_ACCOUNTS` of the batch's `ProductEvent`
+        /// accounts continuing from head to tail, followed by its `EventPage` accounts in
+        /// page order, unused pages included. The batch's units, unit tree, handler grants,
+        /// bill of materials and component usages go in any step and are closed too.
+        /// Repeat until everything is folded and closed, then call `finalize_archive`.
+        pub fn archive_product_batch<'info>(
+            ctx: Context<'_, '_, 'info, 'info, ArchiveProductBatch<'info>>,
+            batch_id: [u8; 32],
+        ) -> Result<()> {
+            ctx.accounts.archive(batch_id, ctx.remaining_accounts, ctx.bumps)
+        }
+
+        /// Close an archived batch once its whole history is folded into the tombstone
+        pub fn finalize_archive(
+            ctx: Context<FinalizeArchive>,
+            batch_id: [u8; 32],
+        ) -> Result<()> {
+            ctx.accounts.finalize(batch_id)
+        }
+
+        /// Statuses the batch may legally move to next, returned to the caller
+        pub fn next_product_states(
+            ctx: Context<ViewBatchStatus>,
+            batch_id: [u8; 32],
+        ) -> Result<Vec<ProductStatus>> {
+            ctx.accounts.next_states(batch_id)
+        }
+
+        /// Declare the component batches consumed by an assembled batch, drawing each
+        /// quantity from its component. Remaining accounts are, per component, the batch,
+        /// the signer's `BatchHandler` grant when another owner's batch, and the usage PDA.
+        pub fn declare_bill_of_materials<'info>(
+            ctx: Context<'_, '_, 'info, 'info, DeclareBillOfMaterials<'info>>,
+            batch_id: [u8; 32],
+            quantities: Vec<u64>,
+        ) -> Result<()> {
+            ctx.accounts.declare(batch_id, quantities, ctx.remaining_accounts, ctx.bumps)
+        }
+
+        /// Record on a bill of materials that one of its components was recalled. The
+        /// component's `Recalled` status comes from a rollup `event_log` update.
+        pub fn flag_component_recall(ctx: Context<FlagComponentRecall>) -> Result<()> {
+            ctx.accounts.flag()
+        }
+
+        /// Allow another profile to log events on one of the signer's batches
+        pub fn grant_batch_access(
+            ctx: Context<GrantBatchAccess>,
+            batch_id: [u8; 32],
+        ) -> Result<()> {
+            ctx.accounts.grant(batch_id, ctx.bumps)
+        }
+
+        pub fn revoke_batch_access(
+            ctx: Context<RevokeBatchAccess>,
+            batch_id: [u8; 32],
+        ) -> Result<()> {
+            ctx.accounts.revoke(batch_id)
+        }
+
+        //create event 
+
+        #[allow(clippy::too_many_arguments)]
+        pub fn create_event(
+            ctx: Context<CreateEvent>,
+            batch_id: [u8; 32],
+            event_id: [u8; 32],
+            event_type: EventType,
+            metadata_ipfs: Option<String>,
+            order_status: OrderStatus,
+            previous_event: Option<Pubkey>,
+            quantity: Option<u64>,
+            location: Option<Location>,
+        ) -> Result<()> {
+           ctx.accounts.create_event(batch_id, event_id, event_type, metadata_ipfs, order_status, previous_event, quantity, location, ctx.bumps)
+        }
+
+        /// Open a page for a batch's rollup history, can be done ahead of time. Pages are
+        /// opened in order, any page after the first needs `previous_event_page`.
+        pub fn open_event_page(
+            ctx: Context<OpenEventPage>,
+            _batch_id: [u8; 32],
+            page_index: u32,
+        ) -> Result<()> {
+            ctx.accounts.open(page_index, ctx.bumps)
+        }
+
+        /// Delegate a further event page while the batch is already on the rollup, so
+        /// `event_log` can move on once the current page fills up
+        pub fn delegate_event_page(
+            ctx: Context<DelegateEventPage>,
+            _batch_id: [u8; 32],
+            page_index: u32,
+        ) -> Result<()> {
+            ctx.accounts.delegate_page(page_index)
+        }
+
+        /// Commit a filled event page back to the base layer, the batch stays delegated
+        pub fn undelegate_event_page(
+            ctx: Context<UndelegateEventPage>,
+            _batch_id: [u8; 32],
+            page_index: u32,
+        ) -> Result<()> {
+            ctx.accounts.undelegate_page(page_index)
+        }
+
+        /// delegate batch and its current event page
+        pub fn delegate_product(
+            ctx: Context<DelegateProduct>,
+            batch_id: [u8; 32],
+            page_index: u32,
+        ) -> Result<()> {
+           ctx.accounts.delegate_to_rollup(batch_id, page_index)
+        }
+
+        //event log 
+
+         #[allow(clippy::too_many_arguments)]
+         pub fn event_log (
+            ctx: Context<RollupEventLog>,
+            batch_id: [u8; 32],
+            page_index: u32,
+            new_product_status: Option<ProductStatus>,
+            new_order_status: Option<OrderStatus>, 
+            event_type: EventType,
+            metadata_ipfs: Option<String>,
+            quantity: Option<u64>,
+            location: Option<Location>,
+        ) -> Result<()> {
+          ctx.accounts.update_supply_chain_state(batch_id, page_index, new_product_status, new_order_status, event_type, metadata_ipfs, quantity, location)
+        }
+
+         pub fn undelegate_product(
+            ctx: Context<UndelegateProduct>,
+            batch_id: [u8; 32],
+            page_index: u32,
+        ) -> Result<()> {
+           ctx.accounts.undelegate(batch_id, page_index)
+        }
 
-    //event log 
-
-     pub fn event_log (
-        ctx: Context<RollupEventLog>,
-        batch_id: [u8; 32],
-        event_id: [u8; 32],
-        new_product_status: Option<ProductStatus>,
-        new_order_status: Option<OrderStatus>, 
-        new_event_type: Option<EventType>,
-        previous_event: Option<Pubkey>,
-        next_event: Option<Pubkey>,
-        metadata_ipfs: Option<String>,
-    ) -> Result<()> {
-      ctx.accounts.update_supply_chain_state(batch_id, event_id, new_product_status, new_order_status, new_event_type, previous_event, next_event, metadata_ipfs)
-    }
-    
-     pub fn undelegate_product(
-        ctx: Context<UndelegateProduct>,
-        batch_id: [u8; 32],
-        event_id: [u8; 32],
-    ) -> Result<()> {
-       ctx.accounts.undelegate(batch_id, event_id)
     }
- 
 }
+
+pub use program_entry::*;
+// The undelegation instruction `#[ephemeral]` injects looks its client accounts up at the crate root
+use program_entry::cassegrain::__client_accounts_initialize_after_undelegation;
@@ -0,0 +1,175 @@
+//! Append-only Merkle tree over compressed unit serials.
+//!
+//! Leaves and inner nodes are hashed with different prefixes so a node can
+//! never be passed off as a leaf. Empty positions hold the zero hash of
+//! their level, which keeps the tree at a fixed `UNIT_TREE_DEPTH`.
+
+use anchor_lang::solana_program::hash::hashv;
+use crate::consts::UNIT_TREE_DEPTH;
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+pub fn hash_leaf(serial_number: &[u8; 32]) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, serial_number]).to_bytes()
+}
+
+pub fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Root of an empty subtree for every level, `zero_hashes()[0]` is an empty leaf
+pub fn zero_hashes() -> [[u8; 32]; UNIT_TREE_DEPTH + 1] {
+    let mut zeros = [[0u8; 32]; UNIT_TREE_DEPTH + 1];
+    for level in 0..UNIT_TREE_DEPTH {
+        zeros[level + 1] = hash_node(&zeros[level], &zeros[level]);
+    }
+    zeros
+}
+
+/// Inserts `leaf` at position `index` and returns the new root. `frontier`
+/// keeps the last left sibling seen on every level, which is all the state
+/// an append-only tree needs.
+pub fn append_leaf(
+    frontier: &mut [[u8; 32]; UNIT_TREE_DEPTH],
+    zeros: &[[u8; 32]; UNIT_TREE_DEPTH + 1],
+    index: u32,
+    leaf: [u8; 32],
+) -> [u8; 32] {
+    let mut node = leaf;
+    let mut position = index;
+    for level in 0..UNIT_TREE_DEPTH {
+        if position & 1 == 0 {
+            frontier[level] = node;
+            node = hash_node(&node, &zeros[level]);
+        } else {
+            node = hash_node(&frontier[level], &node);
+        }
+        position /= 2;
+    }
+    node
+}
+
+pub fn verify_proof(root: &[u8; 32], leaf: [u8; 32], index: u32, proof: &[[u8; 32]]) -> bool {
+    if proof.len() != UNIT_TREE_DEPTH {
+        return false;
+    }
+    let mut node = leaf;
+    let mut position = index;
+    for sibling in proof {
+        node = if position & 1 == 0 {
+            hash_node(&node, sibling)
+        } else {
+            hash_node(sibling, &node)
+        };
+        position /= 2;
+    }
+    node == *root
+}
+
+/// Off-chain helper: root over `serial_numbers` in insertion order, matches
+/// the root the program stores after appending the same serials.
+pub fn compute_root(serial_numbers: &[[u8; 32]]) -> [u8; 32] {
+    let zeros = zero_hashes();
+    let mut frontier = [[0u8; 32]; UNIT_TREE_DEPTH];
+    let mut root = zeros[UNIT_TREE_DEPTH];
+    for (index, serial_number) in serial_numbers.iter().enumerate() {
+        root = append_leaf(&mut frontier, &zeros, index as u32, hash_leaf(serial_number));
+    }
+    root
+}
+
+/// Off-chain helper: proof for the serial at `index`, to pass to
+/// `verify_unit_membership`. Returns `None` when `index` is out of range.
+pub fn build_proof(serial_numbers: &[[u8; 32]], index: usize) -> Option<Vec<[u8; 32]>> {
+    if index >= serial_numbers.len() {
+        return None;
+    }
+    let zeros = zero_hashes();
+    let mut level_nodes: Vec<[u8; 32]> = serial_numbers.iter().map(hash_leaf).collect();
+    let mut position = index;
+    let mut proof = Vec::with_capacity(UNIT_TREE_DEPTH);
+
+    for zero in zeros.iter().take(UNIT_TREE_DEPTH) {
+        let sibling = position ^ 1;
+        proof.push(level_nodes.get(sibling).copied().unwrap_or(*zero));
+
+        level_nodes = level_nodes
+            .chunks(2)
+            .map(|pair| hash_node(&pair[0], pair.get(1).unwrap_or(zero)))
+            .collect();
+        position /= 2;
+    }
+
+    Some(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serials(count: usize) -> Vec<[u8; 32]> {
+        (0..count)
+            .map(|i| {
+                let mut serial = [0u8; 32];
+                serial[..8].copy_from_slice(&(i as u64).to_le_bytes());
+                serial
+            })
+            .collect()
+    }
+
+    #[test]
+    fn proofs_round_trip() {
+        let serials = serials(11);
+        let root = compute_root(&serials);
+
+        // First, second, a power of two and the last leaf
+        for index in [0, 1, 8, serials.len() - 1] {
+            let proof = build_proof(&serials, index).unwrap();
+            assert!(verify_proof(&root, hash_leaf(&serials[index]), index as u32, &proof));
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_index() {
+        let serials = serials(11);
+        let root = compute_root(&serials);
+        let proof = build_proof(&serials, 1).unwrap();
+
+        assert!(!verify_proof(&root, hash_leaf(&serials[1]), 0, &proof));
+        assert!(!verify_proof(&root, hash_leaf(&serials[1]), 3, &proof));
+    }
+
+    #[test]
+    fn rejects_wrong_sibling() {
+        let serials = serials(11);
+        let root = compute_root(&serials);
+        let mut proof = build_proof(&serials, 4).unwrap();
+        proof[0][0] ^= 1;
+
+        assert!(!verify_proof(&root, hash_leaf(&serials[4]), 4, &proof));
+    }
+
+    #[test]
+    fn rejects_out_of_range_and_short_proofs() {
+        let serials = serials(3);
+        let root = compute_root(&serials);
+        let proof = build_proof(&serials, 2).unwrap();
+
+        assert!(build_proof(&serials, 3).is_none());
+        assert!(!verify_proof(&root, hash_leaf(&serials[2]), 2, &proof[1..]));
+    }
+
+    #[test]
+    fn compute_root_matches_sequential_appends() {
+        let serials = serials(11);
+        let zeros = zero_hashes();
+        let mut frontier = [[0u8; 32]; UNIT_TREE_DEPTH];
+
+        assert_eq!(compute_root(&[]), zeros[UNIT_TREE_DEPTH]);
+        for (index, serial) in serials.iter().enumerate() {
+            let root = append_leaf(&mut frontier, &zeros, index as u32, hash_leaf(serial));
+            assert_eq!(root, compute_root(&serials[..=index]));
+        }
+    }
+}
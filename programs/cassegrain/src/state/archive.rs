@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use crate::state::{ProductBatch, ProductStatus};
+use crate::consts::MAX_PARENT_BATCHES;
+
+/// What is left of a batch once it and its events are closed. Filled in over
+/// several `archive_product_batch` steps and sealed by `finalize_archive`.
+#[account]
+#[derive(InitSpace)]
+pub struct BatchArchive {
+    pub batch: Pubkey,
+    pub batch_id: [u8; 32],
+    pub manufacturer: Pubkey,
+    pub final_status: ProductStatus,
+    /// Copied from the batch so an archived split or merge output keeps its provenance
+    #[max_len(MAX_PARENT_BATCHES)]
+    pub parent_batches: Vec<Pubkey>,
+    pub total_events: u32,
+    pub events_archived: u32,
+    pub rollup_records: u32,
+    pub records_archived: u32,
+    /// Pages closed so far, unused pages opened after the last record included
+    pub pages_archived: u32,
+    /// Accounts hanging off the batch closed along with it, checked against
+    /// the batch's counters by `finalize_archive`
+    pub units_closed: u32,
+    pub unit_tree_closed: bool,
+    pub handlers_closed: u16,
+    /// The bill of materials and its component usages
+    pub bom_accounts_closed: u8,
+    /// Next event to fold, only meaningful once `events_archived > 0`
+    pub next_event: Option<Pubkey>,
+    /// Replay of the batch history chain so far
+    pub history_digest: [u8; 32],
+    /// Hash chain over every archived event and event page, sealed with the batch data
+    pub digest: [u8; 32],
+    pub created_at: i64,
+    /// `None` until `finalize_archive` closes the batch
+    pub archived_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl BatchArchive {
+    /// Extends the digest with one serialized event or event page
+    pub fn digest_account(digest: &[u8; 32], account_data: &[u8]) -> [u8; 32] {
+        hashv(&[digest, account_data]).to_bytes()
+    }
+
+    /// Last link of the digest, over the serialized batch as it is closed
+    pub fn seal(digest: &[u8; 32], batch_data: &[u8]) -> [u8; 32] {
+        hashv(&[digest, batch_data]).to_bytes()
+    }
+
+    /// The event the next step has to start from, `None` once the chain is done
+    pub fn expected_event(&self, batch: &ProductBatch) -> Option<Pubkey> {
+        if self.events_archived == 0 {
+            batch.head_event
+        } else {
+            self.next_event
+        }
+    }
+
+    /// Position of the next event or record to fold
+    pub fn position(&self) -> u32 {
+        self.events_archived + self.records_archived
+    }
+
+    /// History fully replayed and matching the batch
+    pub fn is_complete(&self, batch: &ProductBatch) -> bool {
+        self.expected_event(batch).is_none()
+            && self.records_archived == batch.rollup_records
+            && self.history_digest == batch.history_digest
+    }
+
+    /// Every unit, grant and bill of materials account of the batch is closed
+    pub fn dependents_closed(&self, batch: &ProductBatch) -> bool {
+        let bom_accounts = if batch.bom_components > 0 {
+            batch.bom_components + 1
+        } else {
+            0
+        };
+
+        self.units_closed == batch.units_registered
+            && self.unit_tree_closed == (batch.unit_leaf_count > 0)
+            && self.handlers_closed == batch.handlers_granted
+            && self.bom_accounts_closed == bom_accounts
+    }
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::consts::MAX_BOM_COMPONENTS;
+
+/// Component batches consumed by an assembled batch
+#[account]
+#[derive(InitSpace)]
+pub struct BillOfMaterials {
+    pub assembly: Pubkey,
+    pub assembly_batch_id: [u8; 32],
+    pub manufacturer: Pubkey,
+    #[max_len(MAX_BOM_COMPONENTS)]
+    pub components: Vec<BomComponent>,
+    /// Components flagged as recalled after the BOM was declared
+    pub recalled_components: u8,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, InitSpace)]
+pub struct BomComponent {
+    pub batch: Pubkey,
+    pub quantity: u64,
+    pub recalled: bool,
+}
+
+/// Reverse link from a component to one assembly using it. `component` sits
+/// right after the discriminator so every downstream assembly of a recalled
+/// batch can be found with a single memcmp filter.
+#[account]
+#[derive(InitSpace)]
+pub struct ComponentUsage {
+    pub component: Pubkey,
+    pub assembly: Pubkey,
+    pub bill_of_materials: Pubkey,
+    pub quantity: u64,
+    pub bump: u8,
+}
@@ -1,10 +1,18 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
 use crate::state::*;
+use crate::error::CassegrainError;
+use crate::consts::EVENT_PAGE_SIZE;
 
 #[account]
 #[derive(InitSpace)]
 pub struct ProductEvent {
+    /// Caller supplied reference, the address derives from `sequence`
     pub event_id: [u8; 32],           
+    /// Position in the batch, taken from `ProductBatch::total_events` at creation
+    pub sequence: u32,
+    /// Position in the batch history shared with rollup records
+    pub history_index: u32,
     pub batch_id: [u8; 32],          
     pub product_event_type: EventType,     
     pub actor: Pubkey,              
@@ -15,5 +23,427 @@ pub struct ProductEvent {
     pub order_status: OrderStatus, 
     pub previous_event: Option<Pubkey>, 
     pub next_event: Option<Pubkey>, 
+    /// Set when the event concerns a single unit rather than the whole batch
+    pub unit: Option<Pubkey>,
+    /// Quantity drawn from the batch by shipments, sales and destruction
+    pub quantity: Option<u64>,
+    pub location: Option<Location>,
+    /// `event_hash` of the previous event, zero for the head
+    pub prev_hash: [u8; 32],
+    /// Hash over `prev_hash` and this event's contents
+    pub event_hash: [u8; 32],
     pub bumps: u8    
 }
+
+/// A link of a batch hash chain, either an event account or a rollup record
+pub trait ChainLink {
+    fn history_index(&self) -> u32;
+    fn prev_hash(&self) -> [u8; 32];
+    fn stored_hash(&self) -> [u8; 32];
+    fn compute_hash(&self) -> Result<[u8; 32]>;
+
+    /// Checks this link extends `prev_hash` and still matches its stored hash
+    fn verify_link(&self, prev_hash: &[u8; 32]) -> Result<[u8; 32]> {
+        require!(
+            self.prev_hash() == *prev_hash && self.compute_hash()? == self.stored_hash(),
+            CassegrainError::InvalidEventChain
+        );
+        Ok(self.stored_hash())
+    }
+}
+
+impl ChainLink for ProductEvent {
+    fn history_index(&self) -> u32 {
+        self.history_index
+    }
+
+    fn prev_hash(&self) -> [u8; 32] {
+        self.prev_hash
+    }
+
+    fn stored_hash(&self) -> [u8; 32] {
+        self.event_hash
+    }
+
+    /// Hashes everything an auditor cares about, `next_event` is left out as it
+    /// is only filled in when the following event arrives
+    fn compute_hash(&self) -> Result<[u8; 32]> {
+        let mut contents = Vec::new();
+        (
+            self.event_id,
+            self.sequence,
+            self.history_index,
+            self.batch_id,
+            self.product_event_type,
+            self.actor,
+            self.timestamp,
+            &self.metadata_ipfs,
+            self.order_status,
+            self.previous_event,
+            self.unit,
+            self.quantity,
+            &self.location,
+        )
+            .serialize(&mut contents)?;
+        Ok(hashv(&[&self.prev_hash, &contents]).to_bytes())
+    }
+}
+
+/// One page of a batch's append-only rollup history, `EVENT_PAGE_SIZE` records long
+#[account]
+#[derive(InitSpace)]
+pub struct EventPage {
+    pub batch: Pubkey,
+    pub page_index: u32,
+    #[max_len(EVENT_PAGE_SIZE)]
+    pub records: Vec<EventRecord>,
+    pub bump: u8,
+}
+
+/// A rollup update, written once and never modified
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, InitSpace)]
+pub struct EventRecord {
+    /// Position in the batch's rollup history, page `sequence / EVENT_PAGE_SIZE`
+    pub sequence: u32,
+    /// Position in the batch history shared with event accounts
+    pub history_index: u32,
+    pub event_type: EventType,
+    pub product_status: ProductStatus,
+    pub order_status: OrderStatus,
+    pub actor: Pubkey,
+    pub timestamp: i64,
+    #[max_len(32)]
+    pub metadata_ipfs: Option<String>,
+    pub quantity: Option<u64>,
+    pub location: Option<Location>,
+    /// `record_hash` of the previous record, zero for the first
+    pub prev_hash: [u8; 32],
+    pub record_hash: [u8; 32],
+}
+
+impl ChainLink for EventRecord {
+    fn history_index(&self) -> u32 {
+        self.history_index
+    }
+
+    fn prev_hash(&self) -> [u8; 32] {
+        self.prev_hash
+    }
+
+    fn stored_hash(&self) -> [u8; 32] {
+        self.record_hash
+    }
+
+    fn compute_hash(&self) -> Result<[u8; 32]> {
+        let mut contents = Vec::new();
+        (
+            self.sequence,
+            self.history_index,
+            self.event_type,
+            self.product_status,
+            self.order_status,
+            self.actor,
+            self.timestamp,
+            &self.metadata_ipfs,
+            self.quantity,
+            &self.location,
+        )
+            .serialize(&mut contents)?;
+        Ok(hashv(&[&self.prev_hash, &contents]).to_bytes())
+    }
+}
+
+/// Replays the batch history from `digest`, taking the event or record whose
+/// `history_index` comes next. Returns the digest reproduced and the number of
+/// events and records consumed, stops at the first gap so callers can resume.
+pub fn replay_history<'a>(
+    mut digest: [u8; 32],
+    mut position: u32,
+    events: &mut std::iter::Peekable<impl Iterator<Item = &'a ProductEvent>>,
+    records: &mut std::iter::Peekable<impl Iterator<Item = &'a EventRecord>>,
+) -> Result<([u8; 32], u32, u32)> {
+    let (mut events_replayed, mut records_replayed) = (0, 0);
+    loop {
+        if let Some(event) = events.next_if(|event| event.history_index == position) {
+            digest = event.verify_link(&digest)?;
+            events_replayed += 1;
+        } else if let Some(record) = records.next_if(|record| record.history_index == position) {
+            digest = record.verify_link(&digest)?;
+            records_replayed += 1;
+        } else {
+            return Ok((digest, events_replayed, records_replayed));
+        }
+        position += 1;
+    }
+}
+
+/// Replays `events`, ordered head to tail, and the records of `pages`, in page
+/// order, as one chain against the digest stored on `batch`. Any rewritten,
+/// dropped, reordered or missing entry breaks it.
+pub fn verify_history(batch: &ProductBatch, events: &[ProductEvent], pages: &[EventPage]) -> Result<()> {
+    let mut event_iter = events.iter().peekable();
+    let mut record_iter = pages.iter().flat_map(|page| page.records.iter()).peekable();
+    let (digest, events_replayed, records_replayed) =
+        replay_history([0u8; 32], 0, &mut event_iter, &mut record_iter)?;
+    require!(
+        event_iter.peek().is_none()
+            && record_iter.peek().is_none()
+            && events_replayed == batch.total_events
+            && records_replayed == batch.rollup_records
+            && digest == batch.history_digest,
+        CassegrainError::InvalidEventChain
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch() -> ProductBatch {
+        let manufacturer = ManufacturerProfile {
+            company_name: "Acme".to_string(),
+            business_type: BusinessType::Manufacturer,
+            owner: Pubkey::new_unique(),
+            certifications: String::new(),
+            is_verified: true,
+            verified_by: None,
+            verified_at: None,
+            verification_cid: None,
+            active_batches: 1,
+            suspension: None,
+            bump: 0,
+        };
+        ProductBatch::new(
+            [7u8; 32],
+            &manufacturer,
+            ProductCategory::Electronics,
+            None,
+            100,
+            UnitOfMeasure::Pieces,
+            0,
+            0,
+        )
+    }
+
+    fn config() -> CassegrainConfig {
+        CassegrainConfig {
+            authority: Pubkey::new_unique(),
+            pending_authority: None,
+            is_paused: false,
+            product_registration_fee: 0,
+            fee_treasury: Pubkey::new_unique(),
+            max_events_per_product: 1000,
+            max_products_per_manufacturer: 10,
+            min_event_interval: 0,
+            max_batch_size: 100,
+            verifiers: Vec::new(),
+            council: None,
+            bump: 0,
+        }
+    }
+
+    /// Logs `count` entries the way `create_event` and `event_log` do, `is_record`
+    /// decides for each position whether it is a rollup record or an event account
+    fn history(
+        batch: &mut ProductBatch,
+        count: u32,
+        is_record: impl Fn(u32) -> bool,
+    ) -> (Vec<ProductEvent>, Vec<EventPage>) {
+        let config = config();
+        let mut events = Vec::new();
+        let mut records = Vec::new();
+        for i in 0..count {
+            if is_record(i) {
+                let record = EventRecord {
+                    sequence: 0,
+                    history_index: 0,
+                    event_type: EventType::LocationUpdate,
+                    product_status: ProductStatus::InTransit,
+                    order_status: OrderStatus::Shipped,
+                    actor: Pubkey::new_unique(),
+                    timestamp: i as i64,
+                    metadata_ipfs: None,
+                    quantity: None,
+                    location: None,
+                    prev_hash: [0u8; 32],
+                    record_hash: [0u8; 32],
+                };
+                records.push(batch.append_record(&config, record).unwrap());
+            } else {
+                let mut event = ProductEvent {
+                    event_id: [i as u8; 32],
+                    sequence: batch.total_events,
+                    history_index: batch.history_len(),
+                    batch_id: batch.batch_id,
+                    product_event_type: EventType::LocationUpdate,
+                    actor: Pubkey::new_unique(),
+                    timestamp: i as i64,
+                    metadata_ipfs: None,
+                    verification_status: VerificationStatus::Pending,
+                    order_status: OrderStatus::Pending,
+                    previous_event: None,
+                    next_event: None,
+                    unit: None,
+                    quantity: None,
+                    location: None,
+                    prev_hash: batch.history_digest,
+                    event_hash: [0u8; 32],
+                    bumps: 0,
+                };
+                event.event_hash = event.compute_hash().unwrap();
+                batch.history_digest = event.event_hash;
+                batch.record_event(&config, i as i64).unwrap();
+                events.push(event);
+            }
+        }
+        let pages = records
+            .chunks(EVENT_PAGE_SIZE)
+            .enumerate()
+            .map(|(page_index, records)| EventPage {
+                batch: Pubkey::new_unique(),
+                page_index: page_index as u32,
+                records: records.to_vec(),
+                bump: 0,
+            })
+            .collect();
+        (events, pages)
+    }
+
+    fn events_only(batch: &mut ProductBatch, count: u32) -> Vec<ProductEvent> {
+        history(batch, count, |_| false).0
+    }
+
+    fn records_only(batch: &mut ProductBatch, count: u32) -> Vec<EventPage> {
+        history(batch, count, |_| true).1
+    }
+
+    /// Every third entry is an event, the rest rollup records over two pages
+    fn interleaved(batch: &mut ProductBatch) -> (Vec<ProductEvent>, Vec<EventPage>) {
+        history(batch, 27, |i| i % 3 != 0)
+    }
+
+    fn assert_broken(result: Result<()>) {
+        match result {
+            Err(Error::AnchorError(error)) => assert_eq!(
+                error.error_code_number,
+                u32::from(CassegrainError::InvalidEventChain)
+            ),
+            other => panic!("expected InvalidEventChain, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn event_chain_verifies() {
+        let mut batch = batch();
+        let events = events_only(&mut batch, 4);
+        verify_history(&batch, &events, &[]).unwrap();
+    }
+
+    #[test]
+    fn event_chain_rejects_rewritten_event() {
+        let mut batch = batch();
+        let mut events = events_only(&mut batch, 4);
+        events[1].metadata_ipfs = Some("QmRewritten".to_string());
+        assert_broken(verify_history(&batch, &events, &[]));
+    }
+
+    #[test]
+    fn event_chain_rejects_dropped_event() {
+        let mut batch = batch();
+        let mut events = events_only(&mut batch, 4);
+        events.remove(1);
+        assert_broken(verify_history(&batch, &events, &[]));
+    }
+
+    #[test]
+    fn event_chain_rejects_reordered_events() {
+        let mut batch = batch();
+        let mut events = events_only(&mut batch, 4);
+        events.swap(1, 2);
+        assert_broken(verify_history(&batch, &events, &[]));
+    }
+
+    #[test]
+    fn event_chain_rejects_truncated_tail() {
+        let mut batch = batch();
+        let mut events = events_only(&mut batch, 4);
+        events.pop();
+        assert_broken(verify_history(&batch, &events, &[]));
+    }
+
+    #[test]
+    fn event_chain_rejects_prefix_matching_digest() {
+        // A prefix that still hashes to the stored digest has to fail on the count alone
+        let mut batch = batch();
+        let events = events_only(&mut batch, 4);
+        batch.history_digest = events[2].event_hash;
+        assert_broken(verify_history(&batch, &events[..3], &[]));
+    }
+
+    #[test]
+    fn record_chain_verifies_across_pages() {
+        let mut batch = batch();
+        let pages = records_only(&mut batch, EVENT_PAGE_SIZE as u32 + 4);
+        assert_eq!(pages.len(), 2);
+        verify_history(&batch, &[], &pages).unwrap();
+    }
+
+    #[test]
+    fn record_chain_rejects_rewritten_record() {
+        let mut batch = batch();
+        let mut pages = records_only(&mut batch, EVENT_PAGE_SIZE as u32 + 4);
+        pages[0].records[3].quantity = Some(5);
+        assert_broken(verify_history(&batch, &[], &pages));
+    }
+
+    #[test]
+    fn record_chain_rejects_dropped_record() {
+        let mut batch = batch();
+        let mut pages = records_only(&mut batch, EVENT_PAGE_SIZE as u32 + 4);
+        pages[0].records.remove(3);
+        assert_broken(verify_history(&batch, &[], &pages));
+    }
+
+    #[test]
+    fn record_chain_rejects_reordered_records() {
+        let mut batch = batch();
+        let mut pages = records_only(&mut batch, EVENT_PAGE_SIZE as u32 + 4);
+        pages.swap(0, 1);
+        assert_broken(verify_history(&batch, &[], &pages));
+    }
+
+    #[test]
+    fn record_chain_rejects_truncated_tail() {
+        let mut batch = batch();
+        let mut pages = records_only(&mut batch, EVENT_PAGE_SIZE as u32 + 4);
+        pages[1].records.pop();
+        assert_broken(verify_history(&batch, &[], &pages));
+    }
+
+    #[test]
+    fn interleaved_history_verifies() {
+        let mut batch = batch();
+        let (events, pages) = interleaved(&mut batch);
+        assert_eq!((events.len(), pages.len()), (9, 2));
+        verify_history(&batch, &events, &pages).unwrap();
+    }
+
+    #[test]
+    fn interleaved_history_rejects_swapped_event_and_record() {
+        // Claiming the record came before the event changes the committed order
+        let mut batch = batch();
+        let (mut events, mut pages) = interleaved(&mut batch);
+        let record = &mut pages[0].records[0];
+        std::mem::swap(&mut events[1].history_index, &mut record.history_index);
+        assert_broken(verify_history(&batch, &events, &pages));
+    }
+
+    #[test]
+    fn interleaved_history_rejects_missing_events() {
+        let mut batch = batch();
+        let (events, pages) = interleaved(&mut batch);
+        assert_broken(verify_history(&batch, &events[..events.len() - 1], &pages));
+        assert_broken(verify_history(&batch, &[], &pages));
+    }
+}
@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+use crate::consts::{BATCH, MANUFACTURER, MAX_COUNCIL_MEMBERS, TREASURY};
+use crate::error::CassegrainError;
+use crate::state::ConfigUpdate;
+
+/// M-of-N admin council, a singleton once created
+#[account]
+#[derive(InitSpace)]
+pub struct AdminCouncil {
+    #[max_len(MAX_COUNCIL_MEMBERS)]
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub proposal_count: u64,
+    pub bump: u8,
+}
+
+impl AdminCouncil {
+    pub fn is_member(&self, key: &Pubkey) -> bool {
+        self.members.contains(key)
+    }
+
+    pub fn validate_members(members: &[Pubkey], threshold: u8) -> Result<()> {
+        require!(
+            !members.is_empty() && members.len() <= MAX_COUNCIL_MEMBERS,
+            CassegrainError::InvalidCouncil
+        );
+        require!(
+            threshold > 0 && threshold as usize <= members.len(),
+            CassegrainError::InvalidCouncil
+        );
+        for (i, member) in members.iter().enumerate() {
+            require!(!members[i + 1..].contains(member), CassegrainError::InvalidCouncil);
+        }
+        Ok(())
+    }
+
+    /// Approvals from keys that are still members, so removed members stop counting
+    pub fn approvals_counted(&self, proposal: &CouncilProposal) -> usize {
+        proposal
+            .approvals
+            .iter()
+            .filter(|key| self.is_member(key))
+            .count()
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CouncilProposal {
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: CouncilAction,
+    #[max_len(MAX_COUNCIL_MEMBERS)]
+    pub approvals: Vec<Pubkey>,
+    pub created_at: i64,
+    pub executed: bool,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, InitSpace)]
+pub enum CouncilAction {
+    UpdateConfig(ConfigUpdate),
+    SetPaused(bool),
+    SetFeeTreasury(Pubkey),
+    AddVerifier(Pubkey),
+    RemoveVerifier(Pubkey),
+    ChangeMembers {
+        #[max_len(MAX_COUNCIL_MEMBERS)]
+        members: Vec<Pubkey>,
+        threshold: u8,
+    },
+    WithdrawFees {
+        amount: u64,
+        destination: Pubkey,
+    },
+    ProposeAuthority(Pubkey),
+    CancelAuthorityTransfer,
+    SuspendManufacturer {
+        manufacturer: Pubkey,
+        reason_code: u16,
+        expires_at: Option<i64>,
+    },
+    LiftSuspension(Pubkey),
+    RevokeVerification(Pubkey),
+    SetBatchEventLimit {
+        manufacturer: Pubkey,
+        batch_id: [u8; 32],
+        max_events: Option<u32>,
+    },
+}
+
+impl CouncilAction {
+    /// Accounts `execute_proposal` expects in `remaining_accounts`, in order
+    pub fn expected_accounts(&self) -> Vec<Pubkey> {
+        match self {
+            CouncilAction::WithdrawFees { destination, .. } => vec![
+                Pubkey::find_program_address(&[TREASURY], &crate::ID).0,
+                *destination,
+            ],
+            CouncilAction::SuspendManufacturer { manufacturer, .. }
+            | CouncilAction::LiftSuspension(manufacturer)
+            | CouncilAction::RevokeVerification(manufacturer) => vec![
+                Pubkey::find_program_address(&[MANUFACTURER, manufacturer.as_ref()], &crate::ID).0,
+            ],
+            CouncilAction::SetBatchEventLimit { manufacturer, batch_id, .. } => vec![
+                Pubkey::find_program_address(
+                    &[BATCH, manufacturer.as_ref(), batch_id.as_ref()],
+                    &crate::ID,
+                )
+                .0,
+            ],
+            _ => Vec::new(),
+        }
+    }
+}
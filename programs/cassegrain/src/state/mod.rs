@@ -8,4 +8,13 @@ pub mod event;
 pub use event::*;
 
 pub mod utils;
-pub use utils::*;
\ No newline at end of file
+pub use utils::*;
+
+pub mod governance;
+pub use governance::*;
+
+pub mod bom;
+pub use bom::*;
+
+pub mod archive;
+pub use archive::*;
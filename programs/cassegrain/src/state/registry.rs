@@ -1,5 +1,8 @@
 use anchor_lang::prelude::*;
-use crate::state::{ProductCategory, ProductStatus, BusinessType};
+use crate::state::{ProductCategory, ProductStatus, OrderStatus, BusinessType, CassegrainConfig, EventType, EventRecord, ChainLink, UnitOfMeasure};
+use crate::consts::{BATCH, MAX_METADATA_HISTORY, MAX_PARENT_BATCHES, SOLD_RETENTION_PERIOD, UNIT_TREE_DEPTH};
+use crate::error::CassegrainError;
+use crate::merkle;
 
 
 #[account]
@@ -9,6 +12,8 @@ pub struct ProductBatch{
     #[max_len(32)]
     pub manufacturer_name: String,  
     pub status: ProductStatus,   
+    /// Latest order status logged by any event of the batch
+    pub order_status: OrderStatus,
     pub created_at: i64,             
     pub last_updated: i64, 
     #[max_len(32)]         
@@ -17,24 +22,394 @@ pub struct ProductBatch{
     pub category: ProductCategory,    
     pub manufacturer: Pubkey,
     pub event_account: Option<Pubkey>,
+    /// First and last event of the batch's chain, `None` until the first event
+    pub head_event: Option<Pubkey>,
+    pub tail_event: Option<Pubkey>,
     pub total_events: u32,
-    pub batch_size: u8,
+    /// Records appended to the batch's event pages by `event_log`
+    pub rollup_records: u32,
+    /// Hash of the latest event or rollup record, both extend the same chain so
+    /// the order they were logged in is committed too. Zero while there are none.
+    pub history_digest: [u8; 32],
+    /// Replaces `max_events_per_product` for this batch when set by the authority
+    pub max_events_override: Option<u32>,
+    /// Unit accounts created under this batch
+    pub units_registered: u32,
+    /// Root over the compressed unit serials, meaningful once `unit_leaf_count > 0`
+    pub unit_merkle_root: [u8; 32],
+    /// Compressed units, together with `units_registered` never above `batch_size`
+    pub unit_leaf_count: u32,
+    /// Batches this one was split from or merged out of
+    #[max_len(MAX_PARENT_BATCHES)]
+    pub parent_batches: Vec<Pubkey>,
+    /// Batches created from this one by a split or merge
+    pub derived_batches: u16,
+    /// Open `BatchHandler` grants, closed along with the batch when it is archived
+    pub handlers_granted: u16,
+    /// Components of the batch's bill of materials, zero until one is declared
+    pub bom_components: u8,
+    /// Registered quantity, in `unit_of_measure`
+    pub batch_size: u64,
+    pub unit_of_measure: UnitOfMeasure,
+    /// Quantity not yet shipped, sold, destroyed or repackaged
+    pub remaining_quantity: u64,
+    /// Always `batch_size - remaining_quantity`
+    pub consumed_quantity: u64,
+    /// Bumped on every `update_batch_metadata`
+    pub metadata_version: u16,
+    /// Documents `metadata_ipfs` pointed at before each update, oldest first
+    #[max_len(MAX_METADATA_HISTORY)]
+    pub metadata_history: Vec<MetadataRevision>,
     pub bump: u8,
 }
 
-// // redundant for first batch mvp, will be usefull later
-// #[account]
-// #[derive(InitSpace)]
-// pub struct Product {
-//     pub product_id: [u8; 32],  
-//     pub batch_id: [u8; 32], 
-//     pub created_at: i64,             
-//     pub last_updated: i64, 
-//     #[max_len(32)]         
-//     pub metadata_ipfs: Option<String>,
-//     pub current_owner: Option<Pubkey>,        
-//     pub bump: u8,
-// }
+impl ProductBatch {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        batch_id: [u8; 32],
+        manufacturer: &ManufacturerProfile,
+        category: ProductCategory,
+        metadata_ipfs: Option<String>,
+        batch_size: u64,
+        unit_of_measure: UnitOfMeasure,
+        created_at: i64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            batch_id,
+            manufacturer_name: manufacturer.company_name.clone(),
+            status: ProductStatus::Created,
+            order_status: OrderStatus::Pending,
+            created_at,
+            last_updated: created_at,
+            metadata_ipfs,
+            authenticity_verified: false,
+            category,
+            manufacturer: manufacturer.owner,
+            event_account: None,
+            head_event: None,
+            tail_event: None,
+            total_events: 0,
+            rollup_records: 0,
+            history_digest: [0u8; 32],
+            max_events_override: None,
+            units_registered: 0,
+            unit_merkle_root: [0u8; 32],
+            unit_leaf_count: 0,
+            parent_batches: Vec::new(),
+            derived_batches: 0,
+            handlers_granted: 0,
+            bom_components: 0,
+            batch_size,
+            unit_of_measure,
+            remaining_quantity: batch_size,
+            consumed_quantity: 0,
+            metadata_version: 0,
+            metadata_history: Vec::new(),
+            bump,
+        }
+    }
+
+    /// Points the batch at a new metadata document, keeping the replaced one
+    pub fn update_metadata(&mut self, metadata_ipfs: String, timestamp: i64) -> Result<()> {
+        require!(metadata_ipfs.len() <= 32, CassegrainError::InvalidIPFSHash);
+        require!(
+            self.metadata_ipfs.as_ref() != Some(&metadata_ipfs),
+            CassegrainError::InvalidIPFSHash
+        );
+        require!(
+            self.metadata_history.len() < MAX_METADATA_HISTORY,
+            CassegrainError::MetadataHistoryFull
+        );
+
+        if let Some(previous) = self.metadata_ipfs.replace(metadata_ipfs) {
+            self.metadata_history.push(MetadataRevision {
+                version: self.metadata_version,
+                metadata_ipfs: previous,
+                replaced_at: timestamp,
+            });
+        }
+        self.metadata_version = self
+            .metadata_version
+            .checked_add(1)
+            .ok_or(CassegrainError::MathOverflow)?;
+        self.last_updated = timestamp;
+        Ok(())
+    }
+
+    /// Recomputes the PDA from the stored seeds, for batches passed as remaining accounts
+    pub fn address(&self) -> Result<Pubkey> {
+        Pubkey::create_program_address(
+            &[BATCH, self.manufacturer.as_ref(), self.batch_id.as_ref(), &[self.bump]],
+            &crate::ID,
+        )
+        .map_err(|_| error!(CassegrainError::InvalidBatchId))
+    }
+
+    /// Moves the batch to `next` if the transition table allows it
+    pub fn transition_status(&mut self, next: ProductStatus) -> Result<()> {
+        require!(
+            self.status.can_transition_to(next),
+            CassegrainError::InvalidProductStatus
+        );
+        self.status = next;
+        Ok(())
+    }
+
+    /// Moves the batch's order to `next` if the order lifecycle allows it
+    pub fn transition_order_status(&mut self, next: OrderStatus) -> Result<()> {
+        require!(
+            self.order_status.can_transition_to(next),
+            CassegrainError::InvalidOrderStatusTransition
+        );
+        self.order_status = next;
+        Ok(())
+    }
+
+    /// Delivered and destroyed batches are final, sold ones after the retention
+    /// window. Repackaged batches stay open, the `parent_batches` of their
+    /// outputs point at them.
+    pub fn is_archivable(&self, now: i64) -> bool {
+        match self.status {
+            ProductStatus::Delivered | ProductStatus::Destroyed => true,
+            ProductStatus::Sold => now.saturating_sub(self.last_updated) >= SOLD_RETENTION_PERIOD,
+            _ => false,
+        }
+    }
+
+    /// Marks this batch as consumed by a split or merge
+    pub fn mark_repackaged(&mut self, derived: u16, timestamp: i64) -> Result<()> {
+        self.transition_status(ProductStatus::Repackaged)?;
+        self.consume_quantity(self.remaining_quantity)?;
+        self.derived_batches = self
+            .derived_batches
+            .checked_add(derived)
+            .ok_or(CassegrainError::MathOverflow)?;
+        self.last_updated = timestamp;
+        Ok(())
+    }
+
+    /// The manufacturer may always act on its batch, anyone else needs a handler grant
+    pub fn authorize_actor(&self, actor: &Pubkey, handler: Option<&BatchHandler>) -> Result<()> {
+        if self.manufacturer == *actor {
+            return Ok(());
+        }
+        require!(
+            handler.is_some_and(|grant| grant.handler == *actor),
+            CassegrainError::Unauthorized
+        );
+        Ok(())
+    }
+
+    pub fn event_limit(&self, config: &CassegrainConfig) -> u32 {
+        self.max_events_override.unwrap_or(config.max_events_per_product)
+    }
+
+    /// `None` falls back to the config wide `max_events_per_product`
+    pub fn set_event_limit_override(&mut self, max_events: Option<u32>) -> Result<()> {
+        if let Some(limit) = max_events {
+            require!(limit > 0, CassegrainError::InvalidConfigValue);
+        }
+        self.max_events_override = max_events;
+        Ok(())
+    }
+
+    /// Draws `quantity` out of the batch, keeping remaining plus consumed at `batch_size`
+    pub fn consume_quantity(&mut self, quantity: u64) -> Result<()> {
+        require!(
+            quantity <= self.remaining_quantity,
+            CassegrainError::InsufficientQuantity
+        );
+        self.remaining_quantity -= quantity;
+        self.consumed_quantity = self
+            .consumed_quantity
+            .checked_add(quantity)
+            .ok_or(CassegrainError::MathOverflow)?;
+        require!(
+            self.remaining_quantity.checked_add(self.consumed_quantity) == Some(self.batch_size),
+            CassegrainError::QuantityMismatch
+        );
+        Ok(())
+    }
+
+    /// Shipments, sales and destruction must carry a non-zero quantity, other events none
+    pub fn apply_event_quantity(&mut self, event_type: EventType, quantity: Option<u64>) -> Result<()> {
+        match quantity {
+            Some(quantity) if event_type.consumes_quantity() && quantity > 0 => {
+                self.consume_quantity(quantity)
+            }
+            None if !event_type.consumes_quantity() => Ok(()),
+            _ => err!(CassegrainError::QuantityMismatch),
+        }
+    }
+
+    fn ensure_unit_capacity(&self, count: u32) -> Result<()> {
+        require!(
+            self.status.accepts_units(),
+            CassegrainError::InvalidProductStatus
+        );
+        require!(
+            self.unit_of_measure == UnitOfMeasure::Pieces,
+            CassegrainError::InvalidUnit
+        );
+        let total_units = self
+            .units_registered
+            .checked_add(self.unit_leaf_count)
+            .and_then(|units| units.checked_add(count))
+            .ok_or(CassegrainError::MathOverflow)?;
+        require!(
+            total_units as u64 <= self.batch_size,
+            CassegrainError::UnitLimitExceeded
+        );
+        Ok(())
+    }
+
+    /// Reserves room for `count` more unit accounts
+    pub fn add_units(&mut self, count: u32) -> Result<()> {
+        self.ensure_unit_capacity(count)?;
+        self.units_registered += count;
+        Ok(())
+    }
+
+    /// Appends compressed serials to the unit tree and moves the root along
+    pub fn append_unit_serials(
+        &mut self,
+        tree: &mut UnitTree,
+        serial_numbers: &[[u8; 32]],
+    ) -> Result<()> {
+        self.ensure_unit_capacity(serial_numbers.len() as u32)?;
+        require!(
+            self.unit_leaf_count as u64 + serial_numbers.len() as u64 <= 1u64 << UNIT_TREE_DEPTH,
+            CassegrainError::UnitLimitExceeded
+        );
+
+        let zeros = merkle::zero_hashes();
+        for serial_number in serial_numbers {
+            self.unit_merkle_root = merkle::append_leaf(
+                &mut tree.frontier,
+                &zeros,
+                self.unit_leaf_count,
+                merkle::hash_leaf(serial_number),
+            );
+            self.unit_leaf_count += 1;
+        }
+        Ok(())
+    }
+
+    /// Appends `event` to the chain, returns the previous tail for the caller to link
+    pub fn link_event(&mut self, event: Pubkey) -> Option<Pubkey> {
+        if self.head_event.is_none() {
+            self.head_event = Some(event);
+        }
+        self.tail_event.replace(event)
+    }
+
+    /// Events and rollup records logged so far, the position of the next entry
+    /// in the batch history
+    pub fn history_len(&self) -> u32 {
+        self.total_events + self.rollup_records
+    }
+
+    /// Event accounts and rollup records both count against the limit
+    fn ensure_event_capacity(&self, config: &CassegrainConfig) -> Result<()> {
+        let logged = self
+            .total_events
+            .checked_add(self.rollup_records)
+            .and_then(|logged| logged.checked_add(1))
+            .ok_or(CassegrainError::MathOverflow)?;
+        require!(
+            logged <= self.event_limit(config),
+            CassegrainError::EventLimitExceeded
+        );
+        Ok(())
+    }
+
+    /// Counts one more event against the batch limit
+    pub fn record_event(&mut self, config: &CassegrainConfig, timestamp: i64) -> Result<()> {
+        self.ensure_event_capacity(config)?;
+        self.total_events += 1;
+        self.last_updated = timestamp;
+        Ok(())
+    }
+
+    /// Chains `record` onto the rollup history and counts it against the batch limit
+    pub fn append_record(
+        &mut self,
+        config: &CassegrainConfig,
+        mut record: EventRecord,
+    ) -> Result<EventRecord> {
+        self.ensure_event_capacity(config)?;
+        record.sequence = self.rollup_records;
+        record.history_index = self.history_len();
+        record.prev_hash = self.history_digest;
+        record.record_hash = record.compute_hash()?;
+
+        self.rollup_records += 1;
+        self.history_digest = record.record_hash;
+        self.last_updated = record.timestamp;
+        Ok(record)
+    }
+}
+
+/// Grants a non-manufacturer profile the right to log events on a batch
+#[account]
+#[derive(InitSpace)]
+pub struct BatchHandler {
+    pub batch: Pubkey,
+    pub handler: Pubkey,
+    pub granted_by: Pubkey,
+    pub granted_at: i64,
+    pub bump: u8,
+}
+
+/// A single serialized item of a batch
+#[account]
+#[derive(InitSpace)]
+pub struct ProductUnit {
+    pub serial_number: [u8; 32],
+    pub batch: Pubkey,
+    pub batch_id: [u8; 32],
+    pub created_at: i64,
+    pub last_updated: i64,
+    #[max_len(32)]
+    pub metadata_ipfs: Option<String>,
+    pub current_owner: Pubkey,
+    pub bump: u8,
+}
+
+/// Frontier of a batch's unit tree, the root and leaf count live on the batch
+#[account]
+#[derive(InitSpace)]
+pub struct UnitTree {
+    pub batch: Pubkey,
+    pub frontier: [[u8; 32]; UNIT_TREE_DEPTH],
+    pub bump: u8,
+}
+
+/// A superseded metadata document of a batch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, InitSpace)]
+pub struct MetadataRevision {
+    pub version: u16,
+    #[max_len(32)]
+    pub metadata_ipfs: String,
+    pub replaced_at: i64,
+}
+
+/// Output of a split: the new batch id and the quantity moved into it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ChildBatch {
+    pub batch_id: [u8; 32],
+    pub batch_size: u64,
+}
+
+/// Input for one unit of a bulk registration
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct UnitRegistration {
+    pub serial_number: [u8; 32],
+    pub metadata_ipfs: Option<String>,
+    /// Defaults to the signer
+    pub owner: Option<Pubkey>,
+}
 
 
 #[account]
@@ -47,5 +422,176 @@ pub struct ManufacturerProfile {
     #[max_len(32)]
     pub certifications: String,
     pub is_verified: bool,
+    pub verified_by: Option<Pubkey>,
+    pub verified_at: Option<i64>,
+    /// IPFS CID of the evidence reviewed by the verifier
+    #[max_len(32)]
+    pub verification_cid: Option<String>,
+    /// Batches registered by this profile that have not been closed
+    pub active_batches: u32,
+    pub suspension: Option<Suspension>,
     pub bump: u8,
 }
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, InitSpace)]
+pub struct Suspension {
+    pub reason_code: u16,
+    pub suspended_by: Pubkey,
+    pub suspended_at: i64,
+    /// `None` keeps the profile suspended until the authority lifts it
+    pub expires_at: Option<i64>,
+}
+
+impl ManufacturerProfile {
+    pub fn validate_identity(company_name: &str, certifications: &str) -> Result<()> {
+        require!(
+            !company_name.is_empty() && company_name.len() <= 32,
+            CassegrainError::InvalidProfileData
+        );
+        require!(certifications.len() <= 32, CassegrainError::InvalidProfileData);
+        Ok(())
+    }
+
+    pub fn is_suspended(&self, now: i64) -> bool {
+        match self.suspension {
+            Some(Suspension { expires_at: Some(expiry), .. }) => now < expiry,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    pub fn require_active(&self, now: i64) -> Result<()> {
+        require!(!self.is_suspended(now), CassegrainError::ManufacturerSuspended);
+        Ok(())
+    }
+
+    /// A new suspension replaces any earlier one
+    pub fn suspend(
+        &mut self,
+        reason_code: u16,
+        suspended_by: Pubkey,
+        now: i64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        if let Some(expiry) = expires_at {
+            require!(expiry > now, CassegrainError::InvalidSuspensionExpiry);
+        }
+        self.suspension = Some(Suspension {
+            reason_code,
+            suspended_by,
+            suspended_at: now,
+            expires_at,
+        });
+        Ok(())
+    }
+
+    pub fn lift_suspension(&mut self) -> Result<Suspension> {
+        self.suspension
+            .take()
+            .ok_or(error!(CassegrainError::ManufacturerNotSuspended))
+    }
+
+    pub fn open_batch(&mut self, config: &CassegrainConfig) -> Result<()> {
+        require!(
+            self.active_batches < config.max_products_per_manufacturer,
+            CassegrainError::ManufacturerBatchLimitReached
+        );
+        self.active_batches = self
+            .active_batches
+            .checked_add(1)
+            .ok_or(CassegrainError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn clear_verification(&mut self) {
+        self.is_verified = false;
+        self.verified_by = None;
+        self.verified_at = None;
+        self.verification_cid = None;
+    }
+
+    pub fn close_batch(&mut self) -> Result<()> {
+        self.active_batches = self
+            .active_batches
+            .checked_sub(1)
+            .ok_or(CassegrainError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch(batch_size: u64) -> ProductBatch {
+        let manufacturer = ManufacturerProfile {
+            company_name: "Acme".to_string(),
+            business_type: BusinessType::Manufacturer,
+            owner: Pubkey::new_unique(),
+            certifications: String::new(),
+            is_verified: true,
+            verified_by: None,
+            verified_at: None,
+            verification_cid: None,
+            active_batches: 1,
+            suspension: None,
+            bump: 0,
+        };
+        ProductBatch::new(
+            [7u8; 32],
+            &manufacturer,
+            ProductCategory::Electronics,
+            None,
+            batch_size,
+            UnitOfMeasure::Pieces,
+            0,
+            0,
+        )
+    }
+
+    fn assert_conserved(batch: &ProductBatch) {
+        assert_eq!(batch.remaining_quantity + batch.consumed_quantity, batch.batch_size);
+    }
+
+    #[test]
+    fn consume_quantity_conserves_batch_size() {
+        let mut batch = batch(100);
+
+        batch.consume_quantity(30).unwrap();
+        assert_eq!((batch.remaining_quantity, batch.consumed_quantity), (70, 30));
+        assert_conserved(&batch);
+
+        // Drawing the rest empties the batch, a zero draw changes nothing
+        batch.consume_quantity(70).unwrap();
+        batch.consume_quantity(0).unwrap();
+        assert_eq!((batch.remaining_quantity, batch.consumed_quantity), (0, 100));
+        assert_conserved(&batch);
+    }
+
+    #[test]
+    fn consume_quantity_rejects_overdraw() {
+        let mut batch = batch(100);
+        batch.consume_quantity(60).unwrap();
+
+        for quantity in [41, u64::MAX] {
+            match batch.consume_quantity(quantity) {
+                Err(Error::AnchorError(error)) => assert_eq!(
+                    error.error_code_number,
+                    u32::from(CassegrainError::InsufficientQuantity)
+                ),
+                other => panic!("expected InsufficientQuantity, got {other:?}"),
+            }
+        }
+        // A failed draw leaves the batch untouched
+        assert_eq!((batch.remaining_quantity, batch.consumed_quantity), (40, 60));
+        assert_conserved(&batch);
+    }
+
+    #[test]
+    fn consume_quantity_catches_drifted_counters() {
+        let mut batch = batch(100);
+        batch.consumed_quantity = 5;
+
+        assert!(batch.consume_quantity(10).is_err());
+    }
+}
@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use crate::error::CassegrainError;
+use crate::state::Location;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Copy, PartialEq)]
 pub enum ProductStatus {
@@ -12,12 +14,48 @@ pub enum ProductStatus {
     Delivered,
     Recalled,
     Destroyed,
+    /// Split into or merged with other batches, see their `parent_batches`
+    Repackaged,
 }
 
 impl Space for ProductStatus {
     const INIT_SPACE: usize = 1; 
 }
 
+impl ProductStatus {
+    /// The transition table, every status change in the program goes through it
+    pub fn next_states(&self) -> &'static [ProductStatus] {
+        use ProductStatus::*;
+        match self {
+            Registered => &[Created, Manufactured, Recalled, Destroyed],
+            Created => &[Manufactured, Recalled, Destroyed, Repackaged],
+            Manufactured => &[InWarehouse, InTransit, ForSale, Recalled, Destroyed, Repackaged],
+            InTransit => &[InWarehouse, Delivered, Recalled, Destroyed],
+            InWarehouse => &[InTransit, ForSale, Recalled, Destroyed, Repackaged],
+            ForSale => &[Sold, InWarehouse, InTransit, Recalled, Destroyed, Repackaged],
+            Sold => &[InTransit, Delivered, Recalled],
+            Delivered => &[InWarehouse, ForSale, Recalled],
+            Recalled => &[Destroyed],
+            Destroyed | Repackaged => &[],
+        }
+    }
+
+    pub fn can_transition_to(&self, next: ProductStatus) -> bool {
+        self.next_states().contains(&next)
+    }
+
+    /// No status can follow a terminal one
+    pub fn is_terminal(&self) -> bool {
+        self.next_states().is_empty()
+    }
+
+    /// Units can't be added once the goods have reached their end of line
+    pub fn accepts_units(&self) -> bool {
+        use ProductStatus::*;
+        !matches!(self, Delivered | Sold | Destroyed | Repackaged)
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Copy, PartialEq)]
 pub enum ProductCategory {
     Electronics,
@@ -34,6 +72,35 @@ impl Space for ProductCategory {
     const INIT_SPACE: usize = 1; 
 }
 
+/// Unit a batch quantity is counted in. Measured units are stored in
+/// thousandths, so a quantity of 1_500 `Kilograms` means 1.5 kg.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Copy, PartialEq)]
+pub enum UnitOfMeasure {
+    Pieces,
+    Kilograms,
+    Litres,
+    Metres,
+}
+
+impl Space for UnitOfMeasure {
+    const INIT_SPACE: usize = 1; 
+}
+
+impl UnitOfMeasure {
+    /// Decimal places of the stored quantity
+    pub fn decimals(&self) -> u8 {
+        match self {
+            UnitOfMeasure::Pieces => 0,
+            UnitOfMeasure::Kilograms | UnitOfMeasure::Litres | UnitOfMeasure::Metres => 3,
+        }
+    }
+
+    /// `max_batch_size` is counted in whole units, scaled here to the stored quantity
+    pub fn scale_limit(&self, max_batch_size: u64) -> u64 {
+        max_batch_size.saturating_mul(10u64.pow(self.decimals() as u32))
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Copy, PartialEq)]
 pub enum EventType {
     Register,
@@ -49,12 +116,67 @@ pub enum EventType {
     OwnershipTransfer,
     LocationUpdate,
     CustomsCleared,
+    Destroyed,
 }
 
 impl Space for EventType {
     const INIT_SPACE: usize = 1; 
 }
 
+impl EventType {
+    /// Business types allowed to emit this event type
+    pub fn allowed_business_types(&self) -> &'static [BusinessType] {
+        use BusinessType::*;
+        match self {
+            EventType::Register
+            | EventType::Manufactured
+            | EventType::Packaged
+            | EventType::Recalled => &[Manufacturer],
+            EventType::QualityCheck | EventType::QualityFailed => &[QualityInspector],
+            EventType::Shipped | EventType::InTransit | EventType::LocationUpdate => {
+                &[Manufacturer, Distributor, LogisticsProvider]
+            }
+            EventType::Delivered => &[Distributor, LogisticsProvider, Retailer],
+            EventType::CustomsCleared => &[Distributor, LogisticsProvider],
+            EventType::Sold => &[Retailer],
+            EventType::OwnershipTransfer => &[Manufacturer, Distributor, Retailer],
+            EventType::Destroyed => &[Manufacturer, QualityInspector],
+        }
+    }
+
+    /// Event types that must say where the handoff happened
+    pub fn requires_location(&self) -> bool {
+        matches!(self, EventType::LocationUpdate | EventType::Shipped | EventType::Delivered)
+    }
+
+    /// Validates the location carried by an event of this type
+    pub fn check_location(&self, location: Option<&Location>) -> Result<()> {
+        match location {
+            Some(location) => location.validate(),
+            None if self.requires_location() => err!(CassegrainError::InvalidLocationData),
+            None => Ok(()),
+        }
+    }
+
+    /// Event types that carry a quantity drawn from the batch
+    pub fn consumes_quantity(&self) -> bool {
+        matches!(self, EventType::Shipped | EventType::Sold | EventType::Destroyed)
+    }
+
+    pub fn authorize(&self, business_type: BusinessType) -> Result<()> {
+        if self.allowed_business_types().contains(&business_type) {
+            return Ok(());
+        }
+        match self {
+            EventType::QualityCheck | EventType::QualityFailed => {
+                err!(CassegrainError::UnauthorizedQualityCheck)
+            }
+            EventType::CustomsCleared => err!(CassegrainError::UnauthorizedCustomsEvent),
+            _ => err!(CassegrainError::UnauthorizedEventType),
+        }
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Copy, PartialEq)]
 pub enum VerificationStatus {
     Pending,
@@ -85,6 +207,31 @@ impl Space for OrderStatus {
     const INIT_SPACE: usize = 1; 
 }
 
+impl OrderStatus {
+    /// Order lifecycle. `Disputed` is only reachable once goods have shipped and
+    /// `Refunded` only out of a cancellation or a dispute.
+    pub fn next_states(&self) -> &'static [OrderStatus] {
+        use OrderStatus::*;
+        match self {
+            Pending => &[Confirmed, Cancelled],
+            Confirmed => &[Processing, Shipped, Cancelled],
+            Processing => &[Shipped, Cancelled],
+            Shipped => &[InTransit, Delivered, Disputed],
+            InTransit => &[Delivered, Disputed],
+            Delivered => &[Completed, Disputed],
+            Completed => &[Disputed],
+            Cancelled => &[Refunded],
+            Disputed => &[Completed, Refunded],
+            Refunded => &[],
+        }
+    }
+
+    /// Staying in the same status is always allowed
+    pub fn can_transition_to(&self, next: OrderStatus) -> bool {
+        *self == next || self.next_states().contains(&next)
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Copy, PartialEq)]
 pub enum PaymentStatus {
     Pending,
@@ -139,5 +286,162 @@ impl Space for BusinessType {
     const INIT_SPACE: usize = 1; 
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRODUCT_STATUSES: [ProductStatus; 11] = [
+        ProductStatus::Registered,
+        ProductStatus::Created,
+        ProductStatus::Manufactured,
+        ProductStatus::InTransit,
+        ProductStatus::InWarehouse,
+        ProductStatus::ForSale,
+        ProductStatus::Sold,
+        ProductStatus::Delivered,
+        ProductStatus::Recalled,
+        ProductStatus::Destroyed,
+        ProductStatus::Repackaged,
+    ];
+
+    const ORDER_STATUSES: [OrderStatus; 10] = [
+        OrderStatus::Pending,
+        OrderStatus::Confirmed,
+        OrderStatus::Processing,
+        OrderStatus::Shipped,
+        OrderStatus::InTransit,
+        OrderStatus::Delivered,
+        OrderStatus::Completed,
+        OrderStatus::Cancelled,
+        OrderStatus::Disputed,
+        OrderStatus::Refunded,
+    ];
+
+    const EVENT_TYPES: [EventType; 14] = [
+        EventType::Register,
+        EventType::Manufactured,
+        EventType::QualityCheck,
+        EventType::Packaged,
+        EventType::Shipped,
+        EventType::InTransit,
+        EventType::Delivered,
+        EventType::Sold,
+        EventType::Recalled,
+        EventType::QualityFailed,
+        EventType::OwnershipTransfer,
+        EventType::LocationUpdate,
+        EventType::CustomsCleared,
+        EventType::Destroyed,
+    ];
+
+    fn assert_error(result: Result<()>, expected: CassegrainError) {
+        match result {
+            Err(Error::AnchorError(error)) => {
+                assert_eq!(error.error_code_number, u32::from(expected))
+            }
+            other => panic!("expected {expected:?}, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn product_status_transitions() {
+        use ProductStatus::*;
+
+        for status in PRODUCT_STATUSES {
+            // A status never transitions to itself, and nothing leads back to `Registered`
+            assert!(!status.can_transition_to(status));
+            assert!(!status.can_transition_to(Registered));
+            // Any live batch can still be recalled
+            if !matches!(status, Recalled | Destroyed | Repackaged) {
+                assert!(status.can_transition_to(Recalled), "{status:?}");
+            }
+        }
+
+        let terminal: Vec<_> = PRODUCT_STATUSES.into_iter().filter(|s| s.is_terminal()).collect();
+        assert_eq!(terminal, [Destroyed, Repackaged]);
+        assert_eq!(Recalled.next_states(), &[Destroyed]);
+        assert!(Sold.can_transition_to(Delivered));
+        assert!(!Sold.can_transition_to(Repackaged));
+        assert!(!InTransit.can_transition_to(ForSale));
+        assert!(!Registered.can_transition_to(Sold));
+    }
 
+    #[test]
+    fn product_status_accepts_units() {
+        use ProductStatus::*;
 
+        let closed: Vec<_> = PRODUCT_STATUSES.into_iter().filter(|s| !s.accepts_units()).collect();
+        assert_eq!(closed, [Sold, Delivered, Destroyed, Repackaged]);
+    }
+
+    #[test]
+    fn order_status_transitions() {
+        use OrderStatus::*;
+
+        for status in ORDER_STATUSES {
+            assert!(status.can_transition_to(status));
+            if status != Pending {
+                assert!(!status.can_transition_to(Pending), "{status:?}");
+            }
+        }
+
+        // Disputes need shipped goods, refunds a cancellation or a dispute
+        for status in [Pending, Confirmed, Processing, Cancelled, Refunded] {
+            assert!(!status.can_transition_to(Disputed), "{status:?}");
+        }
+        let refundable: Vec<_> = ORDER_STATUSES
+            .into_iter()
+            .filter(|s| *s != Refunded && s.can_transition_to(Refunded))
+            .collect();
+        assert_eq!(refundable, [Cancelled, Disputed]);
+
+        assert!(Refunded.next_states().is_empty());
+        assert!(!Delivered.can_transition_to(Shipped));
+        assert!(!Completed.can_transition_to(Cancelled));
+    }
+
+    #[test]
+    fn event_types_allowed_business_types() {
+        use BusinessType::*;
+
+        for event_type in EVENT_TYPES {
+            let allowed = event_type.allowed_business_types();
+            assert!(!allowed.is_empty(), "{event_type:?}");
+            assert!(!allowed.contains(&Consumer), "{event_type:?}");
+            for business_type in allowed {
+                assert!(event_type.authorize(*business_type).is_ok());
+            }
+        }
+
+        assert_eq!(EventType::QualityCheck.allowed_business_types(), &[QualityInspector]);
+        assert_eq!(EventType::QualityFailed.allowed_business_types(), &[QualityInspector]);
+        assert_eq!(EventType::Recalled.allowed_business_types(), &[Manufacturer]);
+        assert_eq!(EventType::Sold.allowed_business_types(), &[Retailer]);
+    }
+
+    #[test]
+    fn event_types_reject_other_business_types() {
+        use BusinessType::*;
+
+        assert_error(
+            EventType::QualityCheck.authorize(Manufacturer),
+            CassegrainError::UnauthorizedQualityCheck,
+        );
+        assert_error(
+            EventType::QualityFailed.authorize(Distributor),
+            CassegrainError::UnauthorizedQualityCheck,
+        );
+        assert_error(
+            EventType::CustomsCleared.authorize(Retailer),
+            CassegrainError::UnauthorizedCustomsEvent,
+        );
+        assert_error(
+            EventType::Sold.authorize(Manufacturer),
+            CassegrainError::UnauthorizedEventType,
+        );
+        assert_error(
+            EventType::Manufactured.authorize(Consumer),
+            CassegrainError::UnauthorizedEventType,
+        );
+    }
+}
@@ -1,9 +1,13 @@
 use anchor_lang::prelude::*;
+use crate::consts::MAX_VERIFIERS;
+use crate::error::CassegrainError;
 
 #[account]
 #[derive(InitSpace)]
 pub struct CassegrainConfig {
     pub authority: Pubkey,
+    /// Set by `propose_authority`, becomes `authority` once it signs `accept_authority`
+    pub pending_authority: Option<Pubkey>,
     pub is_paused: bool,
     pub product_registration_fee: u64,
     pub fee_treasury: Pubkey,
@@ -11,20 +15,106 @@ pub struct CassegrainConfig {
     pub max_products_per_manufacturer: u32,
     /// Minimum time between events (seconds) - For spam protection
     pub min_event_interval: i64,
-    pub max_batch_size: u8,
+    pub max_batch_size: u64,
+    /// Keys appointed by the authority to verify manufacturer profiles
+    #[max_len(MAX_VERIFIERS)]
+    pub verifiers: Vec<Pubkey>,
+    /// Once set, config changes, pausing and verifier appointments go through council proposals
+    pub council: Option<Pubkey>,
     pub bump: u8, // Bump seed for PDA
 }
 
+impl CassegrainConfig {
+    /// Rejects limits that would leave the network unusable
+    pub fn validate(&self) -> Result<()> {
+        require!(self.max_events_per_product > 0, CassegrainError::InvalidConfigValue);
+        require!(self.max_products_per_manufacturer > 0, CassegrainError::InvalidConfigValue);
+        require!(self.min_event_interval >= 0, CassegrainError::InvalidConfigValue);
+        require!(self.max_batch_size > 0, CassegrainError::InvalidConfigValue);
+        Ok(())
+    }
 
-// #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
-// pub struct Location {
-//     pub latitude: f64,        
-//     pub longitude: f64,       
-//     #[max_len(32)]
-//     pub address: String,         
-//     #[max_len(32)]
-//     pub facility_id: String,    
-// }
+    pub fn apply_update(&mut self, update: &ConfigUpdate) -> Result<()> {
+        if let Some(fee) = update.product_registration_fee {
+            self.product_registration_fee = fee;
+        }
+        if let Some(max_events) = update.max_events_per_product {
+            self.max_events_per_product = max_events;
+        }
+        if let Some(max_products) = update.max_products_per_manufacturer {
+            self.max_products_per_manufacturer = max_products;
+        }
+        if let Some(interval) = update.min_event_interval {
+            self.min_event_interval = interval;
+        }
+        if let Some(max_batch_size) = update.max_batch_size {
+            self.max_batch_size = max_batch_size;
+        }
+        self.validate()
+    }
+
+    pub fn is_verifier(&self, key: &Pubkey) -> bool {
+        self.verifiers.contains(key)
+    }
+
+    pub fn add_verifier(&mut self, verifier: Pubkey) -> Result<()> {
+        require!(!self.is_verifier(&verifier), CassegrainError::VerifierAlreadyExists);
+        require!(self.verifiers.len() < MAX_VERIFIERS, CassegrainError::TooManyVerifiers);
+        self.verifiers.push(verifier);
+        Ok(())
+    }
+
+    pub fn remove_verifier(&mut self, verifier: &Pubkey) -> Result<()> {
+        let index = self
+            .verifiers
+            .iter()
+            .position(|key| key == verifier)
+            .ok_or(CassegrainError::VerifierNotFound)?;
+        self.verifiers.remove(index);
+        Ok(())
+    }
+}
+
+/// Partial update of the tunable config values, `None` leaves a value untouched
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Copy, PartialEq, InitSpace)]
+pub struct ConfigUpdate {
+    pub product_registration_fee: Option<u64>,
+    pub max_events_per_product: Option<u32>,
+    pub max_products_per_manufacturer: Option<u32>,
+    pub min_event_interval: Option<i64>,
+    pub max_batch_size: Option<u64>,
+}
+
+
+/// Position of a handoff, coordinates in degrees scaled by 1e7
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, InitSpace)]
+pub struct Location {
+    pub latitude_e7: i32,
+    pub longitude_e7: i32,
+    /// Warehouse, port or store reference
+    #[max_len(32)]
+    pub facility_id: Option<String>,
+}
+
+impl Location {
+    pub const MAX_LATITUDE_E7: i32 = 90 * 10_000_000;
+    pub const MAX_LONGITUDE_E7: i32 = 180 * 10_000_000;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            (-Self::MAX_LATITUDE_E7..=Self::MAX_LATITUDE_E7).contains(&self.latitude_e7)
+                && (-Self::MAX_LONGITUDE_E7..=Self::MAX_LONGITUDE_E7).contains(&self.longitude_e7),
+            CassegrainError::InvalidLocationData
+        );
+        if let Some(ref facility_id) = self.facility_id {
+            require!(
+                !facility_id.is_empty() && facility_id.len() <= 32,
+                CassegrainError::InvalidLocationData
+            );
+        }
+        Ok(())
+    }
+}
 
 
 // #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
@@ -43,4 +133,56 @@ pub struct CassegrainConfig {
 //     pub country: String,           
 // }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(latitude_e7: i32, longitude_e7: i32) -> Location {
+        Location {
+            latitude_e7,
+            longitude_e7,
+            facility_id: None,
+        }
+    }
+
+    #[test]
+    fn location_accepts_the_limits() {
+        let (lat, lon) = (Location::MAX_LATITUDE_E7, Location::MAX_LONGITUDE_E7);
+        assert_eq!(lat, 900_000_000);
+        assert_eq!(lon, 1_800_000_000);
 
+        for (latitude, longitude) in [(0, 0), (lat, lon), (-lat, -lon), (lat, -lon), (-lat, lon)] {
+            assert!(location(latitude, longitude).validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn location_rejects_values_past_the_limits() {
+        let (lat, lon) = (Location::MAX_LATITUDE_E7, Location::MAX_LONGITUDE_E7);
+
+        for (latitude, longitude) in [
+            (lat + 1, 0),
+            (-lat - 1, 0),
+            (0, lon + 1),
+            (0, -lon - 1),
+            // No positive counterpart, an `abs()` based check would overflow
+            (i32::MIN, 0),
+            (0, i32::MIN),
+            (i32::MAX, i32::MAX),
+        ] {
+            assert!(location(latitude, longitude).validate().is_err());
+        }
+    }
+
+    #[test]
+    fn location_checks_facility_id() {
+        let mut location = location(0, 0);
+
+        location.facility_id = Some("x".repeat(32));
+        assert!(location.validate().is_ok());
+        location.facility_id = Some("x".repeat(33));
+        assert!(location.validate().is_err());
+        location.facility_id = Some(String::new());
+        assert!(location.validate().is_err());
+    }
+}
@@ -87,23 +87,32 @@ describe("Cassegrain Supply Chain", () => {
   let authority: Keypair;
   let manufacturer: Keypair;
   let logistics: Keypair;
+  let verifier: Keypair;
+  let qualityInspector: Keypair;
   let consumer: Keypair;
   
   // PDAs
   let configPda: PublicKey;
   let manufacturerProfilePda: PublicKey;
+  let logisticsProfilePda: PublicKey;
+  let inspectorProfilePda: PublicKey;
+  let logisticsHandlerPda: PublicKey;
+  let inspectorHandlerPda: PublicKey;
   let productBatchPda: PublicKey;
   let productEventPda: PublicKey;
+  let eventPagePda: PublicKey;
 
   // Test data
   const batchId = Array.from(crypto.getRandomValues(new Uint8Array(32)));
   const eventId = Array.from(crypto.getRandomValues(new Uint8Array(32)));
+  const eventSequence = 0; // First event of the batch
+  const eventPageIndex = 0; // First page of the batch's rollup history
   
   // Supply chain data
   const companyName = "TechCorp Manufacturing";
   const certifications = "ISO 9001, FDA Approved";
   const metadataIpfs = "QmTest123Hash456";
-  const batchSize = 30;
+  const batchSize = new anchor.BN(30);
 
   before(async () => {
     console.log("\n🔐 Generating test keypairs...");
@@ -113,12 +122,16 @@ describe("Cassegrain Supply Chain", () => {
       authority = anchor.Wallet.local().payer;
       manufacturer = generateTestKeypair("manufacturer");
       logistics = generateTestKeypair("logistics");
+      verifier = generateTestKeypair("verifier");
+      qualityInspector = generateTestKeypair("quality inspector");
       consumer = generateTestKeypair("consumer");
 
       console.log("✅ Keypairs generated successfully:");
       console.log(`  Authority: ${authority.publicKey.toString()}`);
       console.log(`  Manufacturer: ${manufacturer.publicKey.toString()}`);
       console.log(`  Logistics: ${logistics.publicKey.toString()}`);
+      console.log(`  Verifier: ${verifier.publicKey.toString()}`);
+      console.log(`  Quality Inspector: ${qualityInspector.publicKey.toString()}`);
       console.log(`  Consumer: ${consumer.publicKey.toString()}`);
 
       // For testing, you might need to airdrop SOL to these accounts
@@ -126,6 +139,7 @@ describe("Cassegrain Supply Chain", () => {
       try {
         await baseConnection.requestAirdrop(manufacturer.publicKey, 2 * LAMPORTS_PER_SOL);
         await baseConnection.requestAirdrop(logistics.publicKey, 1 * LAMPORTS_PER_SOL);
+        await baseConnection.requestAirdrop(qualityInspector.publicKey, 1 * LAMPORTS_PER_SOL);
         await baseConnection.requestAirdrop(consumer.publicKey, 1 * LAMPORTS_PER_SOL);
         
         // Wait for airdrops to confirm
@@ -142,7 +156,7 @@ describe("Cassegrain Supply Chain", () => {
 
     // Derive PDAs
     [configPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("config"), authority.publicKey.toBuffer()],
+      [Buffer.from("config")],
       program.programId
     );
 
@@ -151,13 +165,38 @@ describe("Cassegrain Supply Chain", () => {
       program.programId
     );
 
+    [logisticsProfilePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("manufacturer"), logistics.publicKey.toBuffer()],
+      program.programId
+    );
+
+    [inspectorProfilePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("manufacturer"), qualityInspector.publicKey.toBuffer()],
+      program.programId
+    );
+
     [productBatchPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("batch"), Buffer.from(batchId)],
+      [Buffer.from("batch"), manufacturer.publicKey.toBuffer(), Buffer.from(batchId)],
+      program.programId
+    );
+
+    [logisticsHandlerPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("handler"), productBatchPda.toBuffer(), logistics.publicKey.toBuffer()],
+      program.programId
+    );
+
+    [inspectorHandlerPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("handler"), productBatchPda.toBuffer(), qualityInspector.publicKey.toBuffer()],
       program.programId
     );
 
     [productEventPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("event"), Buffer.from(eventId)],
+      [Buffer.from("event"), productBatchPda.toBuffer(), new anchor.BN(eventSequence).toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
+
+    [eventPagePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("event_page"), productBatchPda.toBuffer(), new anchor.BN(eventPageIndex).toArrayLike(Buffer, "le", 4)],
       program.programId
     );
 
@@ -196,11 +235,15 @@ describe("Cassegrain Supply Chain", () => {
               1000, // max events per product
               5000,  // max products per manufacturer
               new anchor.BN(5), // 5 seconds min interval (reasonable for testing)
-              50    // max batch size
+              new anchor.BN(50)    // max batch size
             )
             .accountsPartial({
               authority: authority.publicKey,
               cassegrainConfig: configPda,
+              programData: PublicKey.findProgramAddressSync(
+                [program.programId.toBuffer()],
+                new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+              )[0],
               systemProgram: SystemProgram.programId,
             })
             .signers([authority])
@@ -265,7 +308,9 @@ describe("Cassegrain Supply Chain", () => {
         // Fixed assertions based on actual program structure
         expect(manufacturerProfile.companyName).to.equal(companyName);
         expect(manufacturerProfile.owner.toString()).to.equal(manufacturer.publicKey.toString());
-        expect(manufacturerProfile.isVerified).to.be.true; // Based on program logic
+        if (!isAlreadyRegistered) {
+          expect(manufacturerProfile.isVerified).to.be.false; // Verified later by an appointed verifier
+        }
         console.log("✅ Manufacturer profile ready");
       } catch (error) {
         console.error("Error registering manufacturer:", error);
@@ -273,6 +318,120 @@ describe("Cassegrain Supply Chain", () => {
       }
     });
 
+    it("Appoint Verifier", async () => {
+      try {
+        console.log("🛡️ Appointing manufacturer verifier...");
+
+        const configAccount = await program.account.cassegrainConfig.fetch(configPda);
+        if (configAccount.verifiers.some((key) => key.equals(verifier.publicKey))) {
+          console.log("📋 Verifier already appointed! Skipping...");
+        } else {
+          const tx = await program.methods
+            .addVerifier(verifier.publicKey)
+            .accountsPartial({
+              authority: authority.publicKey,
+              cassegrainConfig: configPda,
+            })
+            .signers([authority])
+            .rpc();
+
+          console.log("Add Verifier tx:", tx);
+          console.log("Explorer:", `https://explorer.solana.com/tx/${tx}?cluster=devnet`);
+        }
+
+        const updatedConfig = await program.account.cassegrainConfig.fetch(configPda);
+        expect(updatedConfig.verifiers.map((key) => key.toString())).to.include(verifier.publicKey.toString());
+        console.log("✅ Verifier appointed");
+      } catch (error) {
+        console.error("Error appointing verifier:", error);
+        throw error;
+      }
+    });
+
+    it("Verify Manufacturer", async () => {
+      try {
+        console.log("🛡️ Verifying manufacturer profile...");
+
+        let manufacturerProfile = await program.account.manufacturerProfile.fetch(manufacturerProfilePda);
+        if (manufacturerProfile.isVerified) {
+          console.log("📋 Manufacturer already verified! Skipping...");
+        } else {
+          const tx = await program.methods
+            .verifyManufacturer("QmVerificationEvidence")
+            .accountsPartial({
+              verifier: verifier.publicKey,
+              cassegrainConfig: configPda,
+              manufacturer: manufacturerProfilePda,
+            })
+            .signers([verifier])
+            .rpc();
+
+          console.log("Verify Manufacturer tx:", tx);
+          console.log("Explorer:", `https://explorer.solana.com/tx/${tx}?cluster=devnet`);
+
+          manufacturerProfile = await program.account.manufacturerProfile.fetch(manufacturerProfilePda);
+        }
+
+        // Batch registration requires a verified profile
+        expect(manufacturerProfile.isVerified).to.be.true;
+        console.log("✅ Manufacturer verified");
+      } catch (error) {
+        console.error("Error verifying manufacturer:", error);
+        throw error;
+      }
+    });
+
+    it("Register Logistics and Quality Inspector Profiles", async () => {
+      try {
+        console.log("🏭 Registering supply chain partner profiles...");
+
+        const partners = [
+          { role: "Logistics", keypair: logistics, profilePda: logisticsProfilePda, businessType: { logisticsProvider: {} }, companyName: "FastFreight Logistics" },
+          { role: "Quality Inspector", keypair: qualityInspector, profilePda: inspectorProfilePda, businessType: { qualityInspector: {} }, companyName: "TrustLab Inspections" },
+        ];
+
+        for (const partner of partners) {
+          let profile = await program.account.manufacturerProfile.fetchNullable(partner.profilePda);
+          if (!profile) {
+            const tx = await program.methods
+              .registerManufacturer(partner.companyName, partner.businessType, "ISO 17020")
+              .accountsPartial({
+                signer: partner.keypair.publicKey,
+                manufacturer: partner.profilePda,
+                cassegrainConfig: configPda,
+                systemProgram: SystemProgram.programId,
+              })
+              .signers([partner.keypair])
+              .rpc();
+            console.log(`Register ${partner.role} tx:`, tx);
+            profile = await program.account.manufacturerProfile.fetch(partner.profilePda);
+          }
+
+          // event_log only accepts verified profiles
+          if (!profile.isVerified) {
+            const tx = await program.methods
+              .verifyManufacturer("QmVerificationEvidence")
+              .accountsPartial({
+                verifier: verifier.publicKey,
+                cassegrainConfig: configPda,
+                manufacturer: partner.profilePda,
+              })
+              .signers([verifier])
+              .rpc();
+            console.log(`Verify ${partner.role} tx:`, tx);
+            profile = await program.account.manufacturerProfile.fetch(partner.profilePda);
+          }
+
+          expect(profile.businessType).to.deep.equal(partner.businessType);
+          expect(profile.isVerified).to.be.true;
+        }
+        console.log("✅ Partner profiles ready");
+      } catch (error) {
+        console.error("Error registering partner profiles:", error);
+        throw error;
+      }
+    });
+
     it("Register Product Batch", async () => {
       try {
         console.log("📦 Registering product batch...");
@@ -282,13 +441,15 @@ describe("Cassegrain Supply Chain", () => {
             Array.from(batchId),
             metadataIpfs,
             { electronics: {} }, // ProductCategory::Electronics
-            batchSize
+            batchSize,
+            { pieces: {} } // UnitOfMeasure::Pieces
           )
           .accountsPartial({
             signer: manufacturer.publicKey,
             authority: authority.publicKey,
             productBatch: productBatchPda,
             cassegrainConfig: configPda,
+            feeTreasury: (await program.account.cassegrainConfig.fetch(configPda)).feeTreasury,
             manufacturer: manufacturerProfilePda,
             systemProgram: SystemProgram.programId,
           })
@@ -302,7 +463,7 @@ describe("Cassegrain Supply Chain", () => {
         
         // Fixed assertions - manufacturer field stores the owner's key from manufacturer profile
         expect(productBatch.manufacturer.toString()).to.equal(manufacturer.publicKey.toString());
-        expect(productBatch.batchSize).to.equal(batchSize);
+        expect(productBatch.batchSize.toNumber()).to.equal(batchSize.toNumber());
         expect(productBatch.manufacturerName).to.equal(companyName);
         expect(productBatch.authenticityVerified).to.be.false; // Initially false
         expect(productBatch.totalEvents).to.equal(0); // No events yet
@@ -313,6 +474,39 @@ describe("Cassegrain Supply Chain", () => {
       }
     });
 
+    it("Grant Batch Access to Partners", async () => {
+      try {
+        console.log("🤝 Granting batch access to logistics and quality inspector...");
+
+        for (const [profilePda, handlerPda] of [
+          [logisticsProfilePda, logisticsHandlerPda],
+          [inspectorProfilePda, inspectorHandlerPda],
+        ]) {
+          const tx = await program.methods
+            .grantBatchAccess(Array.from(batchId))
+            .accountsPartial({
+              signer: manufacturer.publicKey,
+              productBatch: productBatchPda,
+              handlerProfile: profilePda,
+              batchHandler: handlerPda,
+              systemProgram: SystemProgram.programId,
+            })
+            .signers([manufacturer])
+            .rpc();
+          console.log("Grant Batch Access tx:", tx);
+        }
+
+        const logisticsGrant = await program.account.batchHandler.fetch(logisticsHandlerPda);
+        const inspectorGrant = await program.account.batchHandler.fetch(inspectorHandlerPda);
+        expect(logisticsGrant.handler.toString()).to.equal(logistics.publicKey.toString());
+        expect(inspectorGrant.handler.toString()).to.equal(qualityInspector.publicKey.toString());
+        console.log("✅ Batch access granted");
+      } catch (error) {
+        console.error("Error granting batch access:", error);
+        throw error;
+      }
+    });
+
     it("Create Initial Supply Chain Event", async () => {
       try {
         console.log("📝 Creating initial supply chain event...");
@@ -324,13 +518,16 @@ describe("Cassegrain Supply Chain", () => {
             { register: {} }, // EventType::Register
             metadataIpfs,
             { pending: {} }, // OrderStatus::Pending
-            null // no previous event
+            null, // no previous event
+            null, // no quantity
+            null // no location
           )
           .accountsPartial({
             signer: manufacturer.publicKey,
             authority: authority.publicKey,
             events: productEventPda,
             productBatch: productBatchPda,
+            batchOwner: manufacturerProfilePda,
             cassegrainConfig: configPda,
             manufacturer: manufacturerProfilePda,
             systemProgram: SystemProgram.programId,
@@ -366,16 +563,32 @@ describe("Cassegrain Supply Chain", () => {
     it("Delegate Product to Ephemeral Rollup", async () => {
       try {
         console.log("🚀 Delegating product accounts to Magic Block ER...");
+
+        // Rollup updates are appended to this page while the batch is delegated
+        await program.methods
+          .openEventPage(
+            Array.from(batchId),
+            eventPageIndex
+          )
+          .accountsPartial({
+            signer: manufacturer.publicKey,
+            productBatch: productBatchPda,
+            eventPage: eventPagePda,
+            // First page of the batch, nothing to chain onto
+            previousEventPage: null,
+          })
+          .signers([manufacturer])
+          .rpc();
         
         const tx = await program.methods
           .delegateProduct(
             Array.from(batchId),
-            Array.from(eventId)
+            eventPageIndex
           )
           .accountsPartial({
             signer: manufacturer.publicKey,
             productBatch: productBatchPda,
-            productEvent: productEventPda,
+            eventPage: eventPagePda,
           })
           .signers([manufacturer])
           .rpc();
@@ -400,21 +613,30 @@ describe("Cassegrain Supply Chain", () => {
         const updates = [
           {
             description: "Update to Manufacturing Status",
-            productStatus: { created: {} },
+            signer: manufacturer,
+            batchHandler: null, // The batch owner needs no grant
+            productStatus: { manufactured: {} },
             orderStatus: { confirmed: {} },
             eventType: { manufactured: {} }
           },
           {
             description: "Ship from factory",
+            signer: logistics,
+            batchHandler: logisticsHandlerPda,
             productStatus: { inTransit: {} },
             orderStatus: { shipped: {} },
-            eventType: { shipped: {} }
+            eventType: { shipped: {} },
+            quantity: new anchor.BN(10),
+            location: { latitudeE7: 313_000_000, longitudeE7: 1_215_000_000, facilityId: "SHA-FACTORY-01" }
           },
           {
             description: "Final delivery",
+            signer: logistics,
+            batchHandler: logisticsHandlerPda,
             productStatus: { delivered: {} },
             orderStatus: { delivered: {} },
-            eventType: { delivered: {} }
+            eventType: { delivered: {} },
+            location: { latitudeE7: 377_749_000, longitudeE7: -1_224_194_000, facilityId: "SFO-DC-03" }
           }
         ];
 
@@ -428,20 +650,22 @@ describe("Cassegrain Supply Chain", () => {
               ephemeralProgram.methods
                 .eventLog(
                   Array.from(batchId),
-                  Array.from(eventId),
+                  eventPageIndex,
                   update.productStatus,
                   update.orderStatus,
                   update.eventType,
-                  null, // previous_event
-                  null, // next_event
-                  `update_${i + 1}_metadata`
+                  `update_${i + 1}_metadata`,
+                  update.quantity ?? null,
+                  update.location ?? null
                 )
                 .accountsPartial({
-                  signer: logistics.publicKey,
+                  signer: update.signer.publicKey,
                   productBatch: productBatchPda,
-                  productEvent: productEventPda,
+                  eventPage: eventPagePda,
+                  batchOwner: manufacturerProfilePda,
+                  batchHandler: update.batchHandler,
                 }),
-              logistics,
+              update.signer,
               providerEphemeralRollup,
               `Supply Chain Update ${i + 1}`
             );
@@ -454,13 +678,14 @@ describe("Cassegrain Supply Chain", () => {
             // Verify state on ER (optional - may not always work)
             try {
               const batchState = await ephemeralProgram.account.productBatch.fetch(productBatchPda);
-              const eventState = await ephemeralProgram.account.productEvent.fetch(productEventPda);
+              const pageState = await ephemeralProgram.account.eventPage.fetch(eventPagePda);
+              const latestRecord = pageState.records[pageState.records.length - 1];
               
               console.log(`📊 Current State:`);
               console.log(`   Batch Status: ${JSON.stringify(batchState.status)}`);
-              console.log(`   Order Status: ${JSON.stringify(eventState.orderStatus)}`);
-              console.log(`   Event Type: ${JSON.stringify(eventState.productEventType)}`);
-              console.log(`   Total Events: ${batchState.totalEvents}`);
+              console.log(`   Order Status: ${JSON.stringify(latestRecord.orderStatus)}`);
+              console.log(`   Event Type: ${JSON.stringify(latestRecord.eventType)}`);
+              console.log(`   Rollup Records: ${batchState.rollupRecords}`);
               
             } catch (fetchError) {
               console.log(`⚠️ Could not fetch ER state - continuing...`);
@@ -492,20 +717,22 @@ describe("Cassegrain Supply Chain", () => {
           ephemeralProgram.methods
             .eventLog(
               Array.from(batchId),
-              Array.from(eventId),
+              eventPageIndex,
               null, // Don't change product status - just log quality check
-              { confirmed: {} }, // OrderStatus::Confirmed
+              null, // Order already delivered, leave it as is
               { qualityCheck: {} }, // EventType::QualityCheck
+              "final_quality_verification_passed",
               null,
-              null,
-              "final_quality_verification_passed"
+              null
             )
             .accountsPartial({
-              signer: manufacturer.publicKey, // Quality inspector
+              signer: qualityInspector.publicKey, // Only quality inspectors may log QualityCheck
               productBatch: productBatchPda,
-              productEvent: productEventPda,
+              eventPage: eventPagePda,
+              batchOwner: manufacturerProfilePda,
+              batchHandler: inspectorHandlerPda,
             }),
-          manufacturer,
+          qualityInspector,
           providerEphemeralRollup,
           "Final Quality Verification"
         );
@@ -527,6 +754,45 @@ describe("Cassegrain Supply Chain", () => {
       }
     });
 
+    it("Rejects Events Outside the Signer's Role on ER", async () => {
+      const attempts = [
+        { eventType: { qualityCheck: {} }, expectedError: "UnauthorizedQualityCheck" },
+        { eventType: { customsCleared: {} }, expectedError: "UnauthorizedCustomsEvent" },
+      ];
+
+      for (const attempt of attempts) {
+        console.log(`🚫 Manufacturer attempting ${JSON.stringify(attempt.eventType)}...`);
+        let rejected = false;
+        try {
+          await ephemeralProgram.methods
+            .eventLog(
+              Array.from(batchId),
+              eventPageIndex,
+              null,
+              null,
+              attempt.eventType,
+              "unauthorized_attempt",
+              null,
+              null
+            )
+            .accountsPartial({
+              signer: manufacturer.publicKey,
+              productBatch: productBatchPda,
+              eventPage: eventPagePda,
+              batchOwner: manufacturerProfilePda,
+              batchHandler: null,
+            })
+            .signers([manufacturer])
+            .rpc();
+        } catch (error) {
+          rejected = true;
+          expect(error.toString()).to.include(attempt.expectedError);
+        }
+        expect(rejected, `${attempt.expectedError} was not raised`).to.be.true;
+      }
+      console.log("✅ Role restricted events rejected");
+    });
+
     it("Undelegate Product from ER", async () => {
       try {
         console.log("🔄 Undelegating product back to Solana mainnet...");
@@ -536,12 +802,12 @@ describe("Cassegrain Supply Chain", () => {
           ephemeralProgram.methods
             .undelegateProduct(
               Array.from(batchId),
-              Array.from(eventId)
+              eventPageIndex
             )
             .accountsPartial({
               signer: manufacturer.publicKey,
               productBatch: productBatchPda,
-              productEvent: productEventPda,
+              eventPage: eventPagePda,
             }),
           manufacturer,
           providerEphemeralRollup,
@@ -557,11 +823,11 @@ describe("Cassegrain Supply Chain", () => {
         
         try {
           const batchInfo = await provider.connection.getAccountInfo(productBatchPda);
-          const eventInfo = await provider.connection.getAccountInfo(productEventPda);
+          const eventInfo = await provider.connection.getAccountInfo(eventPagePda);
           
           console.log("📊 Account Ownership After Undelegation:");
           console.log(`  Batch owner: ${batchInfo?.owner.toString()}`);
-          console.log(`  Event owner: ${eventInfo?.owner.toString()}`);
+          console.log(`  Event page owner: ${eventInfo?.owner.toString()}`);
           console.log(`  Program ID: ${program.programId.toString()}`);
           
           if (batchInfo?.owner.toString() === program.programId.toString()) {
@@ -603,7 +869,7 @@ describe("Cassegrain Supply Chain", () => {
           
           // Basic verification assertions
           expect(productBatch.manufacturerName).to.equal(companyName);
-          expect(productBatch.batchSize).to.equal(batchSize);
+          expect(productBatch.batchSize.toNumber()).to.equal(batchSize.toNumber());
           expect(productBatch.manufacturer.toString()).to.equal(manufacturer.publicKey.toString());
           expect(productEvent.actor.toString()).to.equal(manufacturer.publicKey.toString());
           